@@ -0,0 +1,34 @@
+//! Helpers for the day crates' build scripts.
+
+use crate::{crypt::{self, InputKey}, input::{alias_path, DIR}};
+use std::{env, path::Path};
+
+/// Name of the cfg set when the real input is available.
+pub const REAL_INPUT: &str = "real_input";
+
+/// Set `cfg(real_input)` when `files/real_input.txt` can be read.
+///
+/// Personal inputs are not in every checkout, so tests that need one are marked
+/// `#[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]` and are
+/// reported as ignored instead of failing when it is missing. An encrypted
+/// input only counts when the key is available, otherwise a warning says why
+/// the tests were skipped.
+pub fn detect_inputs() {
+    println!("cargo::rustc-check-cfg=cfg({REAL_INPUT})");
+    println!("cargo::rerun-if-changed={DIR}");
+    println!("cargo::rerun-if-changed={}", crypt::key_file().display());
+    println!("cargo::rerun-if-env-changed={}", crypt::KEY_ENV);
+
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").expect("Not running in a build script.");
+    let real = alias_path(Path::new(&crate_dir), "real");
+
+    if real.is_file() {
+        println!("cargo::rustc-cfg={REAL_INPUT}");
+    }
+    else if crypt::encrypted_path(&real).is_file() {
+        match InputKey::load() {
+            Ok(_) => println!("cargo::rustc-cfg={REAL_INPUT}"),
+            Err(e) => println!("cargo::warning=Skipping real input tests. {e}"),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Talking to the Advent of Code website.
+//!
+//! Requests are authenticated with the `session` cookie from a logged in
+//! browser, read from `AOC_SESSION`. The base URL can be changed with
+//! `--base-url` or `AOC_BASE_URL`, which is how the tests point the client at
+//! a local stub server.
+
+use crate::{crypt, input, registry::Verdict};
+use clap::Args;
+use std::{env, fs, path::{Path, PathBuf}};
+
+/// Environment variable holding the session cookie.
+pub const SESSION_ENV: &str = "AOC_SESSION";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+pub const DEFAULT_YEAR: u16 = 2024;
+
+/// Sent with every request, as the site asks automated tools to identify themselves.
+const USER_AGENT: &str = concat!("aoc/", env!("CARGO_PKG_VERSION"), " (personal solutions runner)");
+
+/// Command line options for commands that use the website.
+#[derive(Args, Debug, Clone)]
+pub struct SiteOptions {
+    /// Puzzle year.
+    #[arg(long, default_value_t = DEFAULT_YEAR)]
+    pub year: u16,
+
+    /// Address of the website, for testing against a local server.
+    #[arg(long, env = "AOC_BASE_URL", default_value = DEFAULT_BASE_URL)]
+    pub base_url: String,
+}
+
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    year: u16,
+}
+
+impl Client {
+    pub fn new(options: &SiteOptions, session: &str) -> Client {
+        Client {
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+            base_url: options.base_url.trim_end_matches('/').to_string(),
+            session: session.trim().to_string(),
+            year: options.year,
+        }
+    }
+
+    /// A client using the session cookie from `AOC_SESSION`.
+    pub fn from_env(options: &SiteOptions) -> Result<Client, String> {
+        let session = env::var(SESSION_ENV)
+            .map_err(|_| format!("Set {SESSION_ENV} to the session cookie of a logged in browser."))?;
+        Ok(Client::new(options, &session))
+    }
+
+    /// The URL of a page for `day`, e.g. `input` or `answer`.
+    pub fn url(&self, day: u8, page: &str) -> String {
+        format!("{}/{}/day/{day}/{page}", self.base_url, self.year)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+
+    /// Download the puzzle input for `day`.
+    pub fn input(&self, day: u8) -> Result<String, String> {
+        let url = self.url(day, "input");
+        let response = self.agent.get(&url).set("Cookie", &self.cookie()).call().map_err(|e| request_error(&url, e))?;
+        response.into_string().map_err(|e| format!("{url}: {e}"))
+    }
+
+    /// Post `answer` for `day` and `part`, returning what the site made of it.
+    pub fn submit(&self, day: u8, part: u8, answer: &str) -> Result<Outcome, String> {
+        let url = self.url(day, "answer");
+        let response = self.agent.post(&url)
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])
+            .map_err(|e| request_error(&url, e))?;
+        let page = response.into_string().map_err(|e| format!("{url}: {e}"))?;
+        Outcome::parse(&page).ok_or_else(|| format!("{url}: unrecognised response:\n{page}"))
+    }
+}
+
+/// The result of submitting an answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// The answer was checked.
+    Checked(Verdict),
+    /// Submitted too soon after a wrong answer, nothing was checked.
+    Wait { seconds: u64 },
+    /// The part was already solved, nothing was checked.
+    AlreadySolved,
+}
+
+impl Outcome {
+    /// Read the outcome from the page the site answers with.
+    pub fn parse(page: &str) -> Option<Outcome> {
+        if page.contains("That's the right answer") {
+            Some(Outcome::Checked(Verdict::Correct))
+        }
+        else if page.contains("That's not the right answer") {
+            let verdict = match page {
+                p if p.contains("your answer is too high") => Verdict::TooHigh,
+                p if p.contains("your answer is too low") => Verdict::TooLow,
+                _ => Verdict::Wrong,
+            };
+            Some(Outcome::Checked(verdict))
+        }
+        else if page.contains("You gave an answer too recently") {
+            Some(Outcome::Wait { seconds: wait_seconds(page).unwrap_or(60) })
+        }
+        else if page.contains("Did you already complete it?") {
+            Some(Outcome::AlreadySolved)
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// Read `You have 1m 5s left to wait` as 65 seconds.
+fn wait_seconds(page: &str) -> Option<u64> {
+    let end = page.find(" left to wait")?;
+    let start = page[..end].rfind("You have ")? + "You have ".len();
+    page[start..end].split_whitespace().map(|amount| {
+        let (number, unit) = amount.split_at(amount.find(|c: char| !c.is_ascii_digit())?);
+        let number: u64 = number.parse().ok()?;
+        match unit {
+            "h" => Some(number * 3600),
+            "m" => Some(number * 60),
+            "s" => Some(number),
+            _ => None,
+        }
+    }).sum()
+}
+
+fn request_error(url: &str, error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(400, _) => format!("{url}: rejected, check that {SESSION_ENV} is current."),
+        ureq::Error::Status(404, _) => format!("{url}: not found, the puzzle may not be unlocked yet."),
+        ureq::Error::Status(code, _) => format!("{url}: the server answered {code}."),
+        ureq::Error::Transport(e) => format!("{url}: {e}"),
+    }
+}
+
+/// Where `fetch` found or put the input.
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+    /// Already there in plain text or encrypted, so nothing was downloaded.
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Make sure the real input for `day` is in the crate at `crate_dir`, downloading it only if it is missing.
+pub fn fetch(client: &Client, day: u8, crate_dir: &Path) -> Result<Fetched, String> {
+    let path = input::alias_path(crate_dir, "real");
+    for cached in [path.clone(), crypt::encrypted_path(&path)] {
+        if cached.is_file() {
+            return Ok(Fetched::Cached(cached));
+        }
+    }
+
+    let contents = client.input(day)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(Fetched::Downloaded(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub::Stub;
+
+    fn client(stub: &Stub) -> Client {
+        Client::new(&SiteOptions { year: 2024, base_url: format!("{}/", stub.url) }, "abc123\n")
+    }
+
+    #[test]
+    fn test_fetch_downloads_once() {
+        let stub = Stub::start(vec![(200, "3   4\n4   3\n")]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("files/real_input.txt");
+
+        assert_eq!(fetch(&client(&stub), 1, dir.path()), Ok(Fetched::Downloaded(path.clone())));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3   4\n4   3\n");
+        assert_eq!(fetch(&client(&stub), 1, dir.path()), Ok(Fetched::Cached(path)));
+
+        let requests = stub.finish();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2024/day/1/input HTTP/1.1\r\n"), "{}", requests[0]);
+        assert!(requests[0].contains("session=abc123\r\n"), "{}", requests[0]);
+        assert!(requests[0].contains(USER_AGENT), "{}", requests[0]);
+    }
+
+    #[test]
+    fn test_fetch_uses_encrypted_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let encrypted = dir.path().join("files/real_input.txt.enc");
+        fs::create_dir_all(encrypted.parent().unwrap()).unwrap();
+        fs::write(&encrypted, "").unwrap();
+
+        let options = SiteOptions { year: 2024, base_url: "http://127.0.0.1:1".to_string() };
+        assert_eq!(fetch(&Client::new(&options, "abc123"), 5, dir.path()), Ok(Fetched::Cached(encrypted)));
+    }
+
+    #[test]
+    fn test_parse_outcome() {
+        let right = "<article><p>That's the right answer!  You are one gold star closer to saving your vacation.</p></article>";
+        assert_eq!(Outcome::parse(right), Some(Outcome::Checked(Verdict::Correct)));
+        let high = "<article><p>That's not the right answer; your answer is too high.  If you're stuck...</p></article>";
+        assert_eq!(Outcome::parse(high), Some(Outcome::Checked(Verdict::TooHigh)));
+        let low = "<article><p>That's not the right answer; your answer is too low.</p></article>";
+        assert_eq!(Outcome::parse(low), Some(Outcome::Checked(Verdict::TooLow)));
+        let wrong = "<article><p>That's not the right answer.  If you're stuck...</p></article>";
+        assert_eq!(Outcome::parse(wrong), Some(Outcome::Checked(Verdict::Wrong)));
+        let wait = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait.</p></article>";
+        assert_eq!(Outcome::parse(wait), Some(Outcome::Wait { seconds: 65 }));
+        let solved = "<article><p>You don't seem to be solving the right level.  Did you already complete it?</p></article>";
+        assert_eq!(Outcome::parse(solved), Some(Outcome::AlreadySolved));
+        assert_eq!(Outcome::parse("<html>maintenance</html>"), None);
+    }
+
+    #[test]
+    fn test_submit() {
+        let stub = Stub::start(vec![(200, "<p>That's not the right answer; your answer is too low.</p>")]);
+        assert_eq!(client(&stub).submit(7, 2, "1234"), Ok(Outcome::Checked(Verdict::TooLow)));
+
+        let requests = stub.finish();
+        assert!(requests[0].starts_with("POST /2024/day/7/answer HTTP/1.1\r\n"), "{}", requests[0]);
+        assert!(requests[0].ends_with("\r\n\r\nlevel=2&answer=1234"), "{}", requests[0]);
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let stub = Stub::start(vec![(400, "Puzzle inputs differ by user."), (404, "Not Found")]);
+        let dir = tempfile::tempdir().unwrap();
+
+        let error = fetch(&client(&stub), 2, dir.path()).unwrap_err();
+        assert!(error.contains("check that AOC_SESSION is current"), "{error}");
+        let error = fetch(&client(&stub), 25, dir.path()).unwrap_err();
+        assert!(error.contains("not be unlocked yet"), "{error}");
+        assert!(!dir.path().join("files/real_input.txt").exists());
+        stub.finish();
+    }
+}
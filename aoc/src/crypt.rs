@@ -0,0 +1,213 @@
+//! Encrypted storage for personal puzzle inputs.
+//!
+//! Real inputs are committed as `files/real_input.txt.enc`, encrypted with
+//! ChaCha20-Poly1305 under a key that never leaves the machine. The key is
+//! read from the `AOC_KEY` environment variable as hex, or from the `.aoc_key`
+//! file at the root of the repository, which is ignored by git.
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use std::{env, fs, path::{Path, PathBuf}, process, sync::atomic::{AtomicUsize, Ordering}};
+
+/// Environment variable holding the key as hex.
+pub const KEY_ENV: &str = "AOC_KEY";
+
+/// Extension added to an input when it is encrypted.
+pub const EXTENSION: &str = "enc";
+
+/// Written at the start of every encrypted file, so other files are rejected early.
+const MAGIC: &[u8] = b"AOC1";
+
+const NONCE_LEN: usize = 12;
+
+/// The root of the repository, which holds the day crates and the key file.
+pub fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("The aoc crate is inside the repository.").to_path_buf()
+}
+
+/// Where the key is stored when `AOC_KEY` is not set.
+pub fn key_file() -> PathBuf {
+    repo_root().join(".aoc_key")
+}
+
+/// The path of the encrypted copy of `path`.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{EXTENSION}"));
+    PathBuf::from(name)
+}
+
+/// Write `data` to `path` through a temporary file renamed into place, so
+/// readers see either the old contents or all of the new ones.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(".{}.{}.tmp", process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+    let temporary = PathBuf::from(temporary);
+
+    fs::write(&temporary, data)
+        .and_then(|()| fs::rename(&temporary, path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temporary);
+            format!("{}: {e}", path.display())
+        })
+}
+
+/// Whether `path` is missing or older than its encrypted copy.
+fn is_stale(path: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(path), modified(&encrypted_path(path))) {
+        (None, _) => true,
+        (Some(plain), Some(encrypted)) => encrypted > plain,
+        (Some(_), None) => false,
+    }
+}
+
+/// A key for encrypting and decrypting inputs.
+pub struct InputKey(Key);
+
+impl InputKey {
+    /// Make a new random key.
+    pub fn generate() -> InputKey {
+        InputKey(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// Parse a key written as 64 hex digits.
+    pub fn from_hex(hex: &str) -> Result<InputKey, String> {
+        let bytes = hex::decode(hex.trim()).map_err(|e| format!("Invalid key: {e}."))?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|b: Vec<u8>| format!("Invalid key: expected 32 bytes, got {}.", b.len()))?;
+        Ok(InputKey(bytes.into()))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Read the key from `AOC_KEY`, falling back to the key file.
+    pub fn load() -> Result<InputKey, String> {
+        if let Ok(hex) = env::var(KEY_ENV) {
+            return InputKey::from_hex(&hex).map_err(|e| format!("{KEY_ENV}: {e}"));
+        }
+
+        let path = key_file();
+        match fs::read_to_string(&path) {
+            Ok(hex) => InputKey::from_hex(&hex).map_err(|e| format!("{}: {e}", path.display())),
+            Err(_) => Err(format!("No key to decrypt inputs: set {KEY_ENV} or save the key to {}.", path.display())),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.0).encrypt(&nonce, plaintext).expect("Input is too large to encrypt.");
+        [MAGIC, nonce.as_slice(), &ciphertext].concat()
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let data = data.strip_prefix(MAGIC).ok_or("Not an encrypted input.")?;
+        if data.len() < NONCE_LEN {
+            return Err("Encrypted input is truncated.".to_string());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Unable to decrypt input, the key is wrong or the file is corrupt.".to_string())
+    }
+
+    /// Write `path` encrypted next to it, returning the encrypted path.
+    pub fn encrypt_file(&self, path: &Path) -> Result<PathBuf, String> {
+        let plaintext = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let encrypted = encrypted_path(path);
+        write_atomic(&encrypted, &self.encrypt(&plaintext))?;
+        Ok(encrypted)
+    }
+
+    /// Restore `path` from its encrypted copy.
+    pub fn decrypt_file(&self, path: &Path) -> Result<(), String> {
+        let encrypted = encrypted_path(path);
+        let data = fs::read(&encrypted).map_err(|e| format!("{}: {e}", encrypted.display()))?;
+        let plaintext = self.decrypt(&data).map_err(|e| format!("{}: {e}", encrypted.display()))?;
+        write_atomic(path, &plaintext)
+    }
+}
+
+/// Make `path` readable, decrypting it from its encrypted copy if it is
+/// missing or older than that copy.
+///
+/// Returns `Ok(false)` when there is neither.
+pub fn ensure_decrypted(path: &Path) -> Result<bool, String> {
+    if !encrypted_path(path).is_file() {
+        return Ok(path.is_file());
+    }
+    if is_stale(path) {
+        match InputKey::load() {
+            Ok(key) => key.decrypt_file(path)?,
+            // Without the key, a plain copy fetched or written by hand is still usable.
+            Err(_) if path.is_file() => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = InputKey::generate();
+        let data = key.encrypt(b"1 2 3\n4 5 6\n");
+        assert_ne!(&data[MAGIC.len() + NONCE_LEN..], b"1 2 3\n4 5 6\n");
+        assert_eq!(key.decrypt(&data).unwrap(), b"1 2 3\n4 5 6\n");
+
+        assert!(InputKey::generate().decrypt(&data).is_err());
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.decrypt(&tampered).is_err());
+        assert!(key.decrypt(b"plain text").is_err());
+    }
+
+    #[test]
+    fn test_key_hex() {
+        let key = InputKey::generate();
+        assert_eq!(InputKey::from_hex(&key.to_hex()).unwrap().to_hex(), key.to_hex());
+        assert!(InputKey::from_hex("abcd").is_err());
+        assert!(InputKey::from_hex("not hex").is_err());
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("real_input.txt");
+        fs::write(&path, "secret").unwrap();
+
+        let key = InputKey::generate();
+        assert_eq!(key.encrypt_file(&path).unwrap(), dir.path().join("real_input.txt.enc"));
+        fs::remove_file(&path).unwrap();
+        key.decrypt_file(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2, "no temporary files are left behind");
+    }
+
+    #[test]
+    fn test_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("real_input.txt");
+        assert!(is_stale(&path));
+
+        fs::write(&path, "old").unwrap();
+        assert!(!is_stale(&path));
+
+        let key = InputKey::generate();
+        fs::write(encrypted_path(&path), key.encrypt(b"new")).unwrap();
+        let earlier = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(earlier).unwrap();
+        assert!(is_stale(&path));
+
+        key.decrypt_file(&path).unwrap();
+        assert!(!is_stale(&path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    }
+}
@@ -0,0 +1,122 @@
+//! Differential testing of solver variants.
+//!
+//! Days that solve the same problem in more than one way, e.g. a brute force
+//! and an optimised version, register each of them in [`Variants`] and check
+//! that they agree on inputs drawn from a proptest strategy. When any two
+//! disagree, the input is shrunk and the failure shows the smallest input
+//! found along with what every variant made of it.
+//!
+//! Needs the `differential` feature, which day crates turn on for their tests.
+
+use proptest::{strategy::Strategy, test_runner::{Config, TestCaseError, TestError, TestRunner}};
+use std::fmt;
+
+/// Number of inputs tried when no other number is given.
+pub const CASES: u32 = 512;
+
+type Solve<I, O> = Box<dyn Fn(&I) -> O>;
+
+/// Named ways of computing the same output from the same input.
+pub struct Variants<I, O> {
+    variants: Vec<(&'static str, Solve<I, O>)>,
+}
+
+/// An input that variants disagree on, and each variant's output.
+#[derive(Debug)]
+pub struct Disagreement<I, O> {
+    pub input: I,
+    pub outputs: Vec<(&'static str, O)>,
+}
+
+impl<I: fmt::Debug, O: fmt::Debug> fmt::Display for Disagreement<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Variants disagree on {:?}", self.input)?;
+        for (name, output) in &self.outputs {
+            writeln!(f, "  {name}: {output:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<I: Clone + fmt::Debug, O: PartialEq + fmt::Debug> Variants<I, O> {
+    pub fn new() -> Self {
+        Variants { variants: Vec::new() }
+    }
+
+    /// Register another variant.
+    pub fn add(mut self, name: &'static str, solve: impl Fn(&I) -> O + 'static) -> Self {
+        self.variants.push((name, Box::new(solve)));
+        self
+    }
+
+    /// Every variant's output for `input`.
+    pub fn outputs(&self, input: &I) -> Vec<(&'static str, O)> {
+        self.variants.iter().map(|(name, solve)| (*name, solve(input))).collect()
+    }
+
+    /// Whether every variant gives the same output for `input`.
+    pub fn agree(&self, input: &I) -> bool {
+        let outputs = self.outputs(input);
+        outputs.windows(2).all(|pair| pair[0].1 == pair[1].1)
+    }
+
+    /// Run every variant on `cases` inputs from `strategy`, returning the smallest
+    /// disagreement found.
+    pub fn check<S: Strategy<Value = I>>(&self, strategy: S, cases: u32) -> Result<(), Disagreement<I, O>> {
+        let config = Config { cases, failure_persistence: None, ..Config::default() };
+        let result = TestRunner::new(config).run(&strategy, |input| match self.agree(&input) {
+            true => Ok(()),
+            false => Err(TestCaseError::fail("variants disagree")),
+        });
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(TestError::Fail(_, input)) => Err(Disagreement { outputs: self.outputs(&input), input }),
+            Err(TestError::Abort(reason)) => panic!("Differential test aborted: {reason}"),
+        }
+    }
+
+    /// Check [`CASES`] inputs from `strategy` and panic with the smallest disagreement.
+    pub fn assert_agree<S: Strategy<Value = I>>(&self, strategy: S) {
+        if let Err(disagreement) = self.check(strategy, CASES) {
+            panic!("{disagreement}");
+        }
+    }
+}
+
+impl<I: Clone + fmt::Debug, O: PartialEq + fmt::Debug> Default for Variants<I, O> {
+    fn default() -> Self {
+        Variants::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shrinks_to_smallest_disagreement() {
+        let variants = Variants::new()
+            .add("square", |n: &u32| n * n)
+            .add("capped", |n: &u32| if *n < 37 { n * n } else { 0 })
+            .add("also square", |n: &u32| n.pow(2));
+
+        let disagreement = variants.check(0..1000u32, 1000).unwrap_err();
+        assert_eq!(disagreement.input, 37);
+        assert_eq!(disagreement.outputs, [("square", 1369), ("capped", 0), ("also square", 1369)]);
+        assert_eq!(disagreement.to_string(), "Variants disagree on 37\n  square: 1369\n  capped: 0\n  also square: 1369\n");
+
+        let lists = Variants::new()
+            .add("sum", |v: &Vec<u8>| v.iter().map(|x| *x as u32).sum::<u32>())
+            .add("wrapping sum", |v: &Vec<u8>| v.iter().fold(0u8, |a, x| a.wrapping_add(*x)) as u32);
+        let disagreement = lists.check(proptest::collection::vec(0..=255u8, 0..10), 1000).unwrap_err();
+        assert_eq!(disagreement.input.iter().map(|x| *x as u32).sum::<u32>(), 256, "{disagreement}");
+    }
+
+    #[test]
+    fn test_agree() {
+        let variants = Variants::new().add("double", |n: &u64| n * 2).add("shift", |n: &u64| n << 1);
+        assert!(variants.check(0..1_000_000u64, 200).is_ok());
+        variants.assert_agree(0..10u64);
+    }
+}
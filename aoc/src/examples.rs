@@ -0,0 +1,213 @@
+//! Pulling the examples out of a saved puzzle description.
+//!
+//! Each part of a puzzle is an `<article>` on the page. Its example input is
+//! the first `<pre><code>` block introduced by a paragraph that mentions an
+//! example, and its answer is the last emphasised code, `<code><em>143</em></code>`,
+//! which is how the site highlights the answer to the example. Part 1 falls
+//! back to its first block. Part 2 only gets an input of its own when it
+//! introduces an example that differs from part 1's, in which case it is saved
+//! as `test2`; otherwise it reuses part 1's.
+
+use crate::{input, registry::{Entry, Registry, Verdict}};
+use std::{fs, path::{Path, PathBuf}};
+
+/// The example for one part of a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub part: u8,
+    /// The example input, or `None` when the part reuses the one before it.
+    pub input: Option<String>,
+    pub answer: Option<String>,
+}
+
+/// Find the example of every part described on `page`.
+pub fn extract(page: &str) -> Result<Vec<Example>, String> {
+    let articles = articles(page);
+    if articles.is_empty() {
+        return Err("No puzzle description found, expected an <article> element.".to_string());
+    }
+
+    let mut examples: Vec<Example> = Vec::new();
+    for (part, article) in (1..).zip(articles) {
+        let input = example_block(article)
+            .filter(|(_, introduced)| part == 1 || *introduced)
+            .map(|(block, _)| block)
+            .filter(|block| !examples.iter().any(|e| e.input.as_ref() == Some(block)));
+        if part == 1 && input.is_none() {
+            return Err("Part 1 has no example input.".to_string());
+        }
+        examples.push(Example { part, input, answer: answer(article) });
+    }
+    Ok(examples)
+}
+
+/// The contents of every `<article>` on the page, in order.
+fn articles(page: &str) -> Vec<&str> {
+    let mut articles = Vec::new();
+    let mut rest = page;
+    while let Some(start) = rest.find("<article") {
+        let Some(open_end) = rest[start..].find('>') else { break };
+        let body = &rest[start + open_end + 1..];
+        let end = body.find("</article>").unwrap_or(body.len());
+        articles.push(&body[..end]);
+        rest = &body[end..];
+    }
+    articles
+}
+
+/// The first block in `article` that follows a paragraph mentioning an example,
+/// falling back to the first block, along with whether it was introduced as one.
+fn example_block(article: &str) -> Option<(String, bool)> {
+    let mut first = None;
+    let mut previous_end = 0;
+    let mut rest = article;
+    let mut offset = 0;
+
+    while let Some(start) = rest.find("<pre><code>") {
+        let body = &rest[start + "<pre><code>".len()..];
+        let end = body.find("</code></pre>")?;
+        let block = text(&body[..end]);
+
+        let before = &article[previous_end..offset + start];
+        let paragraph = &before[before.rfind("<p>").unwrap_or(0)..];
+        if paragraph.to_lowercase().contains("example") {
+            return Some((block, true));
+        }
+        first.get_or_insert(block);
+
+        offset += start + "<pre><code>".len() + end + "</code></pre>".len();
+        previous_end = offset;
+        rest = &article[offset..];
+    }
+    first.map(|block| (block, false))
+}
+
+/// The last emphasised code in `article`.
+fn answer(article: &str) -> Option<String> {
+    [("<code><em>", "</em></code>"), ("<em><code>", "</code></em>")].iter()
+        .filter_map(|(open, close)| {
+            let start = article.rfind(open)? + open.len();
+            let end = article[start..].find(close)? + start;
+            Some((start, text(&article[start..end])))
+        })
+        .max_by_key(|(start, _)| *start)
+        .map(|(_, answer)| answer.trim().to_string())
+        .filter(|answer| !answer.is_empty())
+}
+
+/// `html` with tags removed and entities decoded.
+fn text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'").replace("&amp;", "&")
+}
+
+/// Write the examples as inputs of the crate at `crate_dir` and record their
+/// answers in `registry`, returning the files that were written.
+///
+/// Part 1's input is `test` and a separate part 2 input is `test2`. Existing
+/// inputs with different contents are only replaced when `force` is set.
+pub fn save(examples: &[Example], day: u8, crate_dir: &Path, registry: &mut Registry, force: bool) -> Result<Vec<PathBuf>, String> {
+    let mut written = Vec::new();
+    let mut alias = input::DEFAULT;
+
+    for example in examples {
+        if let Some(contents) = &example.input {
+            alias = if example.part == 1 { input::DEFAULT } else { "test2" };
+            let path = input::alias_path(crate_dir, alias);
+            match fs::read_to_string(&path) {
+                Ok(existing) if existing == *contents => (),
+                Ok(_) if !force => return Err(format!("{} already exists with different contents.", path.display())),
+                _ => {
+                    fs::create_dir_all(path.parent().unwrap()).map_err(|e| format!("{}: {e}", path.display()))?;
+                    fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+                    written.push(path);
+                }
+            }
+        }
+
+        if let Some(answer) = &example.answer {
+            registry.record(Entry { day, part: example.part, input: alias.to_string(), answer: answer.clone(), verdict: Verdict::Correct });
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body><main>
+<article class="day-desc"><h2>--- Day 5: Print Queue ---</h2>
+<p>The rules are listed first:</p>
+<pre><code>47|53</code></pre>
+<p>For example:</p>
+<pre><code>47|53
+97|13
+
+75,47,61,53,29
+</code></pre>
+<p>Adding these together produces <code><em>143</em></code>.</p>
+<p>In the <em>first</em> update, <code>75</code> is correct.</p>
+</article>
+<p>Your puzzle answer was <code>4281</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>After fixing it, the update becomes:</p>
+<pre><code>97,<em>75</em>,47</code></pre>
+<p>Adding them up produces <em><code>123</code></em>.</p>
+</article>
+</main></body></html>"#;
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(extract(PAGE).unwrap(), [
+            Example { part: 1, input: Some("47|53\n97|13\n\n75,47,61,53,29\n".to_string()), answer: Some("143".to_string()) },
+            Example { part: 2, input: None, answer: Some("123".to_string()) },
+        ]);
+
+        let same = r#"<article><p>For example:</p><pre><code>1 &lt; 2
+</code></pre><p>gives <code><em>3</em></code></p></article>
+<article><p>With the same example:</p><pre><code>1 &lt; 2
+</code></pre><p>gives <code><em>4</em></code></p></article>"#;
+        assert_eq!(extract(same).unwrap(), [
+            Example { part: 1, input: Some("1 < 2\n".to_string()), answer: Some("3".to_string()) },
+            Example { part: 2, input: None, answer: Some("4".to_string()) },
+        ]);
+
+        let new = same.replace("1 &lt; 2\n</code></pre><p>gives <code><em>4", "3 &amp; 4</code></pre><p>gives <code><em>4");
+        assert_eq!(extract(&new).unwrap()[1].input.as_deref(), Some("3 & 4"));
+
+        assert!(extract("<html></html>").is_err());
+        assert!(extract("<article><p>No example.</p></article>").is_err());
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = Registry::load(&dir.path().join("answers.tsv")).unwrap();
+        let examples = [
+            Example { part: 1, input: Some("1 2\n".to_string()), answer: Some("3".to_string()) },
+            Example { part: 2, input: None, answer: Some("4".to_string()) },
+        ];
+
+        let written = save(&examples, 9, dir.path(), &mut registry, false).unwrap();
+        assert_eq!(written, [dir.path().join("files/test_input.txt")]);
+        assert_eq!(registry.expected(9, 1, "test"), Some("3"));
+        assert_eq!(registry.expected(9, 2, "test"), Some("4"));
+        assert!(save(&examples, 9, dir.path(), &mut registry, false).unwrap().is_empty());
+
+        let changed = [Example { part: 1, input: Some("5 6\n".to_string()), answer: Some("11".to_string()) }];
+        assert!(save(&changed, 9, dir.path(), &mut registry, false).unwrap_err().contains("already exists"));
+        assert_eq!(save(&changed, 9, dir.path(), &mut registry, true).unwrap().len(), 1);
+        assert_eq!(registry.expected(9, 1, "test"), Some("11"));
+        assert_eq!(registry.entries().len(), 2);
+    }
+}
@@ -0,0 +1,203 @@
+//! Generated inputs for stress testing and benchmarking.
+//!
+//! Days that can generate inputs flatten [`GenerateOptions`] into their `Args`.
+//! `--generate SIZE --seed N` writes `files/gen-SIZE-N_input.txt` instead of
+//! solving, and the answers of the day's reference solver next to it in
+//! `files/gen-SIZE-N_answers.tsv`, so `aoc watch --input gen-SIZE-N` can check
+//! against them. Both are ignored by git and kept out of `answers.tsv`, so
+//! generating inputs never changes tracked files or slows down the tests.
+//! The same size and seed always give the same input, on every platform, and
+//! each day tests its own solver against the reference with [`assert_solves`].
+
+use crate::{input, registry::{Entry, Registry, Verdict}};
+use clap::{builder::RangedU64ValueParser, Args};
+use std::{fs, ops::Range, path::{Path, PathBuf}};
+
+/// Command line options for days that can generate inputs.
+#[derive(Args, Debug, Clone, Default)]
+#[command(about = None, long_about = None)]
+pub struct GenerateOptions {
+    /// Generate an input of this size in `files/` and record its answers, instead of solving.
+    #[arg(long = "generate", value_name = "SIZE", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub size: Option<usize>,
+
+    /// Seed for `--generate`.
+    #[arg(long, default_value_t = 0, requires = "size")]
+    pub seed: u64,
+}
+
+/// A generated input and the answers to both parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generated {
+    pub input: String,
+    pub answers: [String; 2],
+}
+
+/// A small seeded random number generator (SplitMix64).
+///
+/// Used instead of an external crate so a seed keeps giving the same input
+/// whatever versions of dependencies are in use.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Cannot pick from an empty range.");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `low..=high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next_u64() % (high - low + 1)
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// The input alias of the generated input of `size` from `seed`.
+pub fn alias(size: usize, seed: u64) -> String {
+    format!("gen-{size}-{seed}")
+}
+
+/// Where the answers to the generated input `alias` are kept, in the same
+/// format as `answers.tsv`.
+pub fn answers_path(crate_dir: &Path, alias: &str) -> PathBuf {
+    crate_dir.join(input::DIR).join(format!("{alias}_answers.tsv"))
+}
+
+/// Write `generated` and its answers into the crate at `crate_dir`,
+/// returning where the input was written.
+pub fn save(crate_dir: &Path, package: &str, size: usize, seed: u64, generated: &Generated) -> Result<PathBuf, String> {
+    let day = crate::day_number(package)
+        .ok_or_else(|| format!("Unable to tell which day '{package}' is."))?;
+    let alias = alias(size, seed);
+    let path = input::alias_path(crate_dir, &alias);
+
+    fs::create_dir_all(path.parent().unwrap()).map_err(|e| format!("{}: {e}", path.display()))?;
+    fs::write(&path, &generated.input).map_err(|e| format!("{}: {e}", path.display()))?;
+
+    let mut registry = Registry::load(&answers_path(crate_dir, &alias))?;
+    for (part, answer) in (1..).zip(&generated.answers) {
+        registry.record(Entry { day, part, input: alias.clone(), answer: answer.clone(), verdict: Verdict::Correct });
+    }
+    registry.save()?;
+    Ok(path)
+}
+
+/// Save a generated input for the calling crate and say where it went, exiting on failure.
+///
+/// `aoc::save_generated!(size, seed, &generated)`.
+#[macro_export]
+macro_rules! save_generated {
+    ($size:expr, $seed:expr, $generated:expr) => {{
+        let (size, seed, generated) = ($size, $seed, $generated);
+        let path = $crate::generate::save(::std::path::Path::new(env!("CARGO_MANIFEST_DIR")), env!("CARGO_PKG_NAME"), size, seed, generated)
+            .unwrap_or_else(|e| $crate::fail(e));
+        println!("Wrote {}, expecting {} and {} with --input {}", path.display(), generated.answers[0], generated.answers[1], $crate::generate::alias(size, seed));
+    }};
+}
+
+/// Check that `solve` finds the generated answers for every seed in `seeds`,
+/// panicking with the seed and input of the first one it gets wrong.
+///
+/// `size` picks the size for each seed, and `solve` is given the part and the
+/// input and returns its answer, so each day's test is one call.
+pub fn assert_solves(seeds: Range<u64>, size: impl Fn(u64) -> usize, generate: impl Fn(usize, u64) -> Generated, solve: impl Fn(u8, &str) -> String) {
+    for seed in seeds {
+        let size = size(seed);
+        let generated = generate(size, seed);
+        for (part, expected) in (1..).zip(&generated.answers) {
+            assert_eq!(&solve(part, &generated.input), expected, "Part {part} wrong for --generate {size} --seed {seed}:\n{}", generated.input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(42);
+        let first: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+        assert_eq!(first, (0..3).map({ let mut rng = Rng::new(42); move |_| rng.next_u64() }).collect::<Vec<_>>());
+        assert_ne!(first[0], Rng::new(43).next_u64());
+
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+        assert!((0..1000).all(|_| (3..=5).contains(&rng.range(3, 5))));
+        assert!((0..1000).any(|_| rng.range(3, 5) == 5));
+
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let generated = Generated { input: "1 2\n".to_string(), answers: ["3".to_string(), "4".to_string()] };
+
+        let path = save(dir.path(), "day_5", 10, 7, &generated).unwrap();
+        assert_eq!(path, dir.path().join("files/gen-10-7_input.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1 2\n");
+        let registry = Registry::load(&dir.path().join("files/gen-10-7_answers.tsv")).unwrap();
+        assert_eq!(registry.expected(5, 2, "gen-10-7"), Some("4"));
+        assert!(save(dir.path(), "new", 10, 7, &generated).is_err());
+    }
+
+    fn sum(size: usize, seed: u64) -> Generated {
+        let numbers: Vec<u64> = (0..size).map({ let mut rng = Rng::new(seed); move |_| rng.range(0, 9) }).collect();
+        let input = numbers.iter().map(u64::to_string).collect::<Vec<_>>().join(" ");
+        Generated { input, answers: [numbers.iter().sum::<u64>().to_string(), numbers.len().to_string()] }
+    }
+
+    fn solve_sum(part: u8, input: &str) -> String {
+        let numbers = input.split_whitespace().map(|n| n.parse::<u64>().unwrap());
+        match part {
+            1 => numbers.sum::<u64>().to_string(),
+            _ => numbers.count().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_assert_solves() {
+        assert_solves(0..10, |seed| seed as usize + 1, sum, solve_sum);
+    }
+
+    #[test]
+    #[should_panic(expected = "Part 2 wrong for --generate 2 --seed 1")]
+    fn test_assert_solves_wrong() {
+        assert_solves(0..10, |seed| seed as usize + 1, sum, |part, input| match part {
+            1 => solve_sum(part, input),
+            _ => input.len().to_string(),
+        });
+    }
+}
@@ -0,0 +1,116 @@
+//! Finding a day's input files.
+//!
+//! Inputs live in the day crate's `files/` directory and are named
+//! `<alias>_input.txt`, e.g. `files/test_input.txt`, `files/test2_input.txt`
+//! and `files/real_input.txt`, so `--input real` works for every day. Lookups
+//! are anchored to the crate directory rather than the working directory, so
+//! binaries and tests behave the same when run from the repository root.
+//! Real inputs may be committed encrypted, see [`crate::crypt`].
+
+use crate::crypt;
+use std::{fs::File, io::{self, BufRead, BufReader}, path::{Path, PathBuf}};
+
+/// Alias used when no input is given.
+pub const DEFAULT: &str = "test";
+
+/// Directory inside each day crate that holds the inputs.
+pub const DIR: &str = "files";
+
+/// The path of the input called `alias` inside the crate at `crate_dir`.
+pub fn alias_path(crate_dir: &Path, alias: &str) -> PathBuf {
+    crate_dir.join(DIR).join(format!("{alias}_input.txt"))
+}
+
+/// Whether `input` looks like an alias rather than a path.
+fn is_alias(input: &str) -> bool {
+    !input.is_empty() && input.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Every path `input` could refer to, in the order they are tried.
+///
+/// An alias is looked up in `files/` first, then `input` is tried as a path
+/// from the working directory and finally as a path from the crate directory.
+pub fn candidates(crate_dir: &Path, input: &str) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if is_alias(input) {
+        candidates.push(alias_path(crate_dir, input));
+    }
+    candidates.push(PathBuf::from(input));
+    if Path::new(input).is_relative() {
+        candidates.push(crate_dir.join(input));
+    }
+    candidates
+}
+
+/// Find the file `input` refers to, or list the paths that were tried.
+///
+/// A file that is only stored encrypted is decrypted next to its encrypted copy.
+pub fn resolve(crate_dir: &Path, input: &str) -> Result<PathBuf, String> {
+    let candidates = candidates(crate_dir, input);
+    for path in &candidates {
+        if crypt::ensure_decrypted(path)? {
+            return Ok(path.clone());
+        }
+    }
+
+    let tried: Vec<String> = candidates.iter().map(|p| format!("  {}", p.display())).collect();
+    Err(format!("Unable to find input '{input}', tried:\n{}", tried.join("\n")))
+}
+
+/// Read from `filename`, or from stdin when there is none, for days that can read either.
+pub fn open(filename: Option<&Path>) -> Box<dyn BufRead> {
+    match filename {
+        Some(filename) => Box::new(BufReader::new(File::open(filename).expect("Unable to read file."))),
+        None => Box::new(io::stdin().lock()),
+    }
+}
+
+/// Resolve an input name relative to the calling crate.
+///
+/// Expands to a `Result<PathBuf, String>`; `input!()` gives the default test input.
+#[macro_export]
+macro_rules! input {
+    () => {
+        $crate::input!($crate::input::DEFAULT)
+    };
+    ($input:expr) => {
+        $crate::input::resolve(::std::path::Path::new(env!("CARGO_MANIFEST_DIR")), $input)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let dir = Path::new("/repo/day_3");
+        assert_eq!(candidates(dir, "test2"), [
+            PathBuf::from("/repo/day_3/files/test2_input.txt"),
+            PathBuf::from("test2"),
+            PathBuf::from("/repo/day_3/test2"),
+        ]);
+        assert_eq!(candidates(dir, "files/real_input.txt"), [
+            PathBuf::from("files/real_input.txt"),
+            PathBuf::from("/repo/day_3/files/real_input.txt"),
+        ]);
+        assert_eq!(candidates(dir, "/tmp/input.txt"), [PathBuf::from("/tmp/input.txt")]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        assert!(resolve(dir, "src/input.rs").is_ok());
+
+        let error = resolve(dir, "missing").unwrap_err();
+        assert!(error.starts_with("Unable to find input 'missing', tried:"), "{error}");
+        assert!(error.contains("missing_input.txt"), "{error}");
+    }
+
+    #[test]
+    fn test_open() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/input.rs");
+        let first = open(Some(&path)).lines().next().unwrap().unwrap();
+        assert_eq!(first, "//! Finding a day's input files.");
+    }
+}
@@ -0,0 +1,158 @@
+//! Shared runner for the day crates.
+//!
+//! Every day flattens [`Options`] into its own `Args` and calls [`init`] first
+//! thing in `main`, so the same flags behave the same way for every day. The
+//! `aoc` binary holds the commands that work across days.
+
+use clap::Args;
+use std::{fmt, fs, path::PathBuf};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+pub mod build;
+#[cfg(feature = "site")]
+pub mod client;
+pub mod crypt;
+#[cfg(any(test, feature = "differential"))]
+pub mod differential;
+pub mod examples;
+pub mod generate;
+pub mod input;
+pub mod registry;
+pub mod report;
+pub mod run;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(all(test, feature = "site"))]
+mod stub;
+
+/// Filter used when neither `--log-level` nor `RUST_LOG` is set.
+const DEFAULT_FILTER: &str = "error";
+
+/// Command line options shared by every day.
+#[derive(Args, Debug, Clone, Default)]
+#[command(about = None, long_about = None)]
+pub struct Options {
+    /// Filename to read the input from, the same as `--input`.
+    #[arg(value_name = "FILENAME", conflicts_with = "input")]
+    pub filename: Option<String>,
+
+    /// Input file, or the alias of one in `files/`: test, test2, real...
+    #[arg(short, long, value_name = "INPUT")]
+    pub input: Option<String>,
+
+    /// Tracing filter, e.g. `info` or `day_5=debug`. Overrides `RUST_LOG`.
+    #[arg(long, value_name = "FILTER", value_parser = parse_filter)]
+    pub log_level: Option<String>,
+
+    /// How to print answers.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: report::Format,
+}
+
+impl Options {
+    /// The input given on the command line, either with `--input` or as the filename.
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref().or(self.filename.as_deref())
+    }
+
+    /// The input given on the command line, or the default test input.
+    pub fn input_name(&self) -> &str {
+        self.input().unwrap_or(input::DEFAULT)
+    }
+}
+
+/// The crate directory of day `day`.
+pub fn day_dir(day: u8) -> PathBuf {
+    crypt::repo_root().join(format!("day_{day}"))
+}
+
+/// The day of the crate called `package`, e.g. 5 for `day_5`.
+pub fn day_number(package: &str) -> Option<u8> {
+    package.strip_prefix("day_")?.parse().ok()
+}
+
+/// Every day that has a crate in the repository, in order.
+pub fn days() -> Vec<u8> {
+    let mut days: Vec<u8> = fs::read_dir(crypt::repo_root()).into_iter().flatten().flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_prefix("day_")?.parse().ok())
+        .collect();
+    days.sort();
+    days
+}
+
+/// Print `message` and exit, for errors in what the user asked for rather than bugs.
+pub fn fail(message: impl fmt::Display) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
+}
+
+/// Check that `s` is a valid tracing filter directive.
+fn parse_filter(s: &str) -> Result<String, String> {
+    EnvFilter::try_new(s).map(|_| s.to_string()).map_err(|e| format!("invalid filter: {e}"))
+}
+
+/// The filter from `--log-level`, then `RUST_LOG`, then the default.
+fn filter(options: &Options) -> EnvFilter {
+    let directives = options.log_level.clone()
+        .or_else(|| std::env::var(EnvFilter::DEFAULT_ENV).ok())
+        .unwrap_or(DEFAULT_FILTER.to_string());
+
+    EnvFilter::try_new(&directives).unwrap_or_else(|e| {
+        eprintln!("Ignoring invalid filter '{directives}': {e}");
+        EnvFilter::new(DEFAULT_FILTER)
+    })
+}
+
+/// Install the global tracing subscriber.
+///
+/// Spans log when they close, along with how long they were busy, so turning
+/// on `info` shows how long parsing and each part took.
+pub fn init(options: &Options) {
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter(options))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).expect("Error registering tracer.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        assert!(parse_filter("info").is_ok());
+        assert!(parse_filter("day_5=debug,warn").is_ok());
+        assert!(parse_filter("day_5=loud").is_err());
+    }
+
+    #[test]
+    fn test_days() {
+        let days = days();
+        assert!(days.windows(2).all(|w| w[0] < w[1]));
+        assert!(days.iter().all(|day| day_dir(*day).join("Cargo.toml").is_file()));
+    }
+
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        options: Options,
+    }
+
+    #[test]
+    fn test_input_name() {
+        use clap::Parser;
+        let input_name = |args: &[&str]| Cli::try_parse_from([&["day_5"], args].concat()).map(|cli| cli.options.input_name().to_string());
+        assert_eq!(input_name(&[]).unwrap(), "test");
+        assert_eq!(input_name(&["files/foo.txt"]).unwrap(), "files/foo.txt");
+        assert_eq!(input_name(&["--input", "real"]).unwrap(), "real");
+        assert!(input_name(&["files/foo.txt", "--input", "real"]).is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        let options = Options { log_level: Some("day_2=info".to_string()), ..Default::default() };
+        assert_eq!(filter(&options).to_string(), "day_2=info");
+    }
+}
@@ -0,0 +1,221 @@
+use aoc::{
+    client::{self, Client, Fetched, Outcome, SiteOptions},
+    crypt::{self, InputKey},
+    day_dir, days, examples, fail, input,
+    registry::{Entry, Registry, Verdict},
+    run, watch,
+};
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Commands shared by every day.", long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage the encrypted copies of the real inputs.
+    Inputs {
+        #[command(subcommand)]
+        action: InputsAction,
+    },
+
+    /// Download the real input for a day into `files/real_input.txt`, unless it is already there.
+    ///
+    /// Uses the session cookie in `AOC_SESSION`.
+    Fetch {
+        #[arg(short, long)]
+        day: u8,
+
+        #[command(flatten)]
+        site: SiteOptions,
+    },
+
+    /// Rerun a day whenever its `src/` or `files/` change, comparing the answers
+    /// with the previous run and with the expected answers in `answers.tsv`.
+    Watch {
+        #[arg(short, long)]
+        day: u8,
+
+        /// Input to run on, an alias or a path.
+        #[arg(short, long, default_value = input::DEFAULT)]
+        input: String,
+    },
+
+    /// Save the examples from a saved puzzle description as a day's test inputs.
+    ///
+    /// The expected answers are recorded in `answers.tsv` against the `test`
+    /// and `test2` inputs.
+    Examples {
+        #[arg(short, long)]
+        day: u8,
+
+        /// The puzzle description, saved from the browser as HTML.
+        page: PathBuf,
+
+        /// Replace test inputs that already exist with different contents.
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Submit the answer to a part of a day and record the verdict in `answers.tsv`.
+    ///
+    /// Answers that are already known to be wrong are not submitted again.
+    Submit {
+        #[arg(short, long)]
+        day: u8,
+
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+
+        /// Submit this instead of running the day on its real input.
+        #[arg(short, long)]
+        answer: Option<String>,
+
+        #[command(flatten)]
+        site: SiteOptions,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum InputsAction {
+    /// Generate a key in the key file, unless one already exists.
+    Key,
+
+    /// Encrypt `files/real_input.txt` of every day, or only of `--day`.
+    Encrypt {
+        #[arg(short, long)]
+        day: Option<u8>,
+    },
+
+    /// Restore `files/real_input.txt` of every day, or only of `--day`, from its encrypted copy.
+    Decrypt {
+        #[arg(short, long)]
+        day: Option<u8>,
+    },
+}
+
+fn main() {
+
+    let cli = Args::parse();
+
+    match cli.command {
+        Command::Inputs { action } => inputs(action),
+        Command::Fetch { day, site } => fetch(day, &site),
+        Command::Watch { day, input } => {
+            if !day_dir(day).is_dir() {
+                fail(format!("There is no crate for day {day} at {}.", day_dir(day).display()));
+            }
+            watch::watch(day, &input).unwrap_or_else(|e| fail(e));
+        }
+        Command::Examples { day, page, force } => save_examples(day, &page, force),
+        Command::Submit { day, part, answer, site } => submit(day, part, answer, &site),
+    }
+}
+
+fn fetch(day: u8, site: &SiteOptions) {
+    let dir = day_dir(day);
+    if !dir.is_dir() {
+        fail(format!("There is no crate for day {day} at {}.", dir.display()));
+    }
+
+    let client = Client::from_env(site).unwrap_or_else(|e| fail(e));
+    match client::fetch(&client, day, &dir).unwrap_or_else(|e| fail(e)) {
+        Fetched::Cached(path) => println!("Already have {}", path.display()),
+        Fetched::Downloaded(path) => println!("Downloaded {}, run `aoc inputs encrypt --day {day}` before committing it.", path.display()),
+    }
+}
+
+fn inputs(action: InputsAction) {
+    match action {
+        InputsAction::Key => {
+            let path = crypt::key_file();
+            if path.exists() {
+                fail(format!("{} already exists.", path.display()));
+            }
+            std::fs::write(&path, InputKey::generate().to_hex()).unwrap_or_else(|e| fail(e));
+            println!("Wrote a new key to {}, keep a copy somewhere safe.", path.display());
+        }
+        InputsAction::Encrypt { day } => {
+            let key = InputKey::load().unwrap_or_else(|e| fail(e));
+            for day in day.map_or_else(days, |d| vec![d]) {
+                let path = input::alias_path(&day_dir(day), "real");
+                if !path.is_file() {
+                    continue;
+                }
+                let encrypted = key.encrypt_file(&path).unwrap_or_else(|e| fail(e));
+                println!("Encrypted {}", encrypted.display());
+            }
+        }
+        InputsAction::Decrypt { day } => {
+            let key = InputKey::load().unwrap_or_else(|e| fail(e));
+            for day in day.map_or_else(days, |d| vec![d]) {
+                let path = input::alias_path(&day_dir(day), "real");
+                if !crypt::encrypted_path(&path).is_file() {
+                    continue;
+                }
+                key.decrypt_file(&path).unwrap_or_else(|e| fail(e));
+                println!("Decrypted {}", path.display());
+            }
+        }
+    }
+}
+
+fn save_examples(day: u8, page: &Path, force: bool) {
+    let dir = day_dir(day);
+    if !dir.is_dir() {
+        fail(format!("There is no crate for day {day} at {}.", dir.display()));
+    }
+
+    let page = std::fs::read_to_string(page).unwrap_or_else(|e| fail(format!("{}: {e}", page.display())));
+    let examples = examples::extract(&page).unwrap_or_else(|e| fail(e));
+    let mut registry = Registry::load(&Registry::default_path()).unwrap_or_else(|e| fail(e));
+    for path in examples::save(&examples, day, &dir, &mut registry, force).unwrap_or_else(|e| fail(e)) {
+        println!("Wrote {}", path.display());
+    }
+    registry.save().unwrap_or_else(|e| fail(e));
+
+    for example in &examples {
+        match &example.answer {
+            Some(answer) => println!("Part {} expects {answer}", example.part),
+            None => println!("Part {} has no emphasised answer, add it to answers.tsv by hand", example.part),
+        }
+    }
+}
+
+fn submit(day: u8, part: u8, answer: Option<String>, site: &SiteOptions) {
+    let answer = answer.unwrap_or_else(|| {
+        let answers = run::run_day(day, "real").unwrap_or_else(|e| fail(e));
+        answers.into_iter().find(|a| a.part == part).map(|a| a.answer)
+            .unwrap_or_else(|| fail(format!("Day {day} did not print an answer for part {part}.")))
+    });
+
+    let mut registry = Registry::load(&Registry::default_path()).unwrap_or_else(|e| fail(e));
+    if registry.expected(day, part, "real") == Some(answer.as_str()) {
+        println!("Day {day} part {part}: {answer} is already known to be correct.");
+        return;
+    }
+    if let Some(reason) = registry.known_wrong(day, part, "real", &answer) {
+        fail(format!("Not submitting {answer} for day {day} part {part}: {reason}."));
+    }
+
+    let client = Client::from_env(site).unwrap_or_else(|e| fail(e));
+    match client.submit(day, part, &answer).unwrap_or_else(|e| fail(e)) {
+        Outcome::Checked(verdict) => {
+            registry.record(Entry { day, part, input: "real".to_string(), answer: answer.clone(), verdict });
+            registry.save().unwrap_or_else(|e| fail(e));
+            let description = match verdict {
+                Verdict::Correct => "correct",
+                Verdict::TooHigh => "too high",
+                Verdict::TooLow => "too low",
+                Verdict::Wrong => "wrong",
+            };
+            println!("Day {day} part {part}: {answer} is {description}.");
+        }
+        Outcome::Wait { seconds } => fail(format!("Answered too recently, try again in {seconds}s. Nothing was submitted.")),
+        Outcome::AlreadySolved => println!("Day {day} part {part} is already solved, nothing was checked."),
+    }
+}
@@ -0,0 +1,283 @@
+//! The local record of known answers.
+//!
+//! `answers.tsv` at the root of the repository holds one answer per line as
+//! `day<TAB>part<TAB>input<TAB>answer<TAB>verdict`, where `input` is the input
+//! alias, e.g. `test` or `real`. It records the verdict of every submission so
+//! wrong answers are never submitted twice, and the expected answers the
+//! tests and runners compare against.
+
+use crate::{crypt, input};
+use std::{fmt, fs, path::{Path, PathBuf}, str::FromStr};
+
+/// What is known about an answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint of which way.
+    Wrong,
+}
+
+impl Verdict {
+    pub fn is_correct(&self) -> bool {
+        *self == Verdict::Correct
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "too_high",
+            Verdict::TooLow => "too_low",
+            Verdict::Wrong => "wrong",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Verdict::Correct),
+            "too_high" => Ok(Verdict::TooHigh),
+            "too_low" => Ok(Verdict::TooLow),
+            "wrong" => Ok(Verdict::Wrong),
+            _ => Err(format!("unknown verdict '{s}'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub day: u8,
+    pub part: u8,
+    pub input: String,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\t{}\t{}\t{}\t{}", self.day, self.part, self.input, self.answer, self.verdict)
+    }
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [day, part, input, answer, verdict] = fields[..] else {
+            return Err(format!("expected 5 tab separated fields, got {}", fields.len()));
+        };
+        Ok(Entry {
+            day: day.parse().map_err(|_| format!("invalid day '{day}'"))?,
+            part: part.parse().map_err(|_| format!("invalid part '{part}'"))?,
+            input: input.to_string(),
+            answer: answer.to_string(),
+            verdict: verdict.parse()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Registry {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    /// Where the registry is kept.
+    pub fn default_path() -> PathBuf {
+        crypt::repo_root().join("answers.tsv")
+    }
+
+    /// Read the registry at `path`, which is empty if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Registry, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+
+        let entries = contents.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| line.parse().map_err(|e| format!("{} line {}: {e}", path.display(), number + 1)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Registry { path: path.to_path_buf(), entries })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| (a.day, a.part, &a.input).cmp(&(b.day, b.part, &b.input)));
+        let contents: String = entries.iter().map(|e| format!("{e}\n")).collect();
+        fs::write(&self.path, contents).map_err(|e| format!("{}: {e}", self.path.display()))
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Add an entry, replacing any earlier verdict on the same answer and,
+    /// for a correct answer, any other answer recorded as correct.
+    pub fn record(&mut self, entry: Entry) {
+        self.entries.retain(|e| {
+            (e.day, e.part, &e.input) != (entry.day, entry.part, &entry.input)
+                || (e.answer != entry.answer && !(e.verdict.is_correct() && entry.verdict.is_correct()))
+        });
+        self.entries.push(entry);
+    }
+
+    /// The correct answer for `day` and `part` on `input`, if known.
+    pub fn expected(&self, day: u8, part: u8, input: &str) -> Option<&str> {
+        self.entries.iter()
+            .find(|e| e.day == day && e.part == part && e.input == input && e.verdict.is_correct())
+            .map(|e| e.answer.as_str())
+    }
+
+    /// Run `solve` on every example input with a correct answer for `day`,
+    /// returning how many were checked and a line for each answer that differs.
+    ///
+    /// The real input is left to each day's own tests, which are slow enough
+    /// without running it twice, and examples that cannot be found are skipped.
+    pub fn check(&self, day: u8, crate_dir: &Path, solve: impl Fn(u8, &Path) -> String) -> (usize, Vec<String>) {
+        let mut checked = 0;
+        let mut wrong = Vec::new();
+        for entry in self.entries.iter().filter(|e| e.day == day && e.input != "real" && e.verdict.is_correct()) {
+            let Ok(path) = input::resolve(crate_dir, &entry.input) else { continue };
+            checked += 1;
+            let answer = solve(entry.part, &path);
+            if answer != entry.answer {
+                wrong.push(format!("part {} on {}: got {answer}, expected {}", entry.part, entry.input, entry.answer));
+            }
+        }
+        (checked, wrong)
+    }
+
+    /// Why `answer` is already known to be wrong, if it is.
+    ///
+    /// Besides answers that were rejected before, numbers at or above a
+    /// "too high" answer or at or below a "too low" one are wrong, and so is
+    /// anything other than the answer that was accepted.
+    pub fn known_wrong(&self, day: u8, part: u8, input: &str, answer: &str) -> Option<String> {
+        if let Some(correct) = self.expected(day, part, input) {
+            return (correct != answer).then(|| format!("the correct answer is already known to be {correct}"));
+        }
+
+        let number: Option<i128> = answer.parse().ok();
+        self.entries.iter()
+            .filter(|e| e.day == day && e.part == part && e.input == input)
+            .find_map(|e| {
+                let known: Option<i128> = e.answer.parse().ok();
+                match (e.verdict, number, known) {
+                    (_, _, _) if e.answer == answer => Some(format!("{answer} was already rejected as {}", e.verdict)),
+                    (Verdict::TooHigh, Some(n), Some(k)) if n >= k => Some(format!("{k} was already too high")),
+                    (Verdict::TooLow, Some(n), Some(k)) if n <= k => Some(format!("{k} was already too low")),
+                    _ => None,
+                }
+            })
+    }
+}
+
+/// Panic unless `solve` gives every correct answer registered for the
+/// examples of the crate called `package`, and at least one could be checked.
+pub fn assert_registered(package: &str, crate_dir: &Path, solve: impl Fn(u8, &Path) -> String) {
+    let day = crate::day_number(package).unwrap_or_else(|| panic!("Unable to tell which day '{package}' is."));
+    let registry = Registry::load(&Registry::default_path()).unwrap_or_else(|e| panic!("{e}"));
+    let (checked, wrong) = registry.check(day, crate_dir, solve);
+    assert!(checked > 0, "No answers for day {day} in {}, add them with `aoc examples`.", registry.path.display());
+    assert!(wrong.is_empty(), "Wrong answers for day {day}:\n  {}", wrong.join("\n  "));
+}
+
+/// Check the calling day against the correct example answers in `answers.tsv`.
+///
+/// `solve` takes the part and the input path and returns the answer as text.
+#[macro_export]
+macro_rules! assert_registered {
+    ($solve:expr) => {
+        $crate::registry::assert_registered(env!("CARGO_PKG_NAME"), ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")), $solve)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(part: u8, answer: &str, verdict: Verdict) -> Entry {
+        Entry { day: 7, part, input: "real".to_string(), answer: answer.to_string(), verdict }
+    }
+
+    #[test]
+    fn test_load_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.tsv");
+
+        let mut registry = Registry::load(&path).unwrap();
+        assert!(registry.entries().is_empty());
+        registry.record(entry(2, "100", Verdict::TooLow));
+        registry.record(entry(1, "3749", Verdict::Correct));
+        registry.record(entry(2, "100", Verdict::Wrong));
+        registry.save().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "7\t1\treal\t3749\tcorrect\n7\t2\treal\t100\twrong\n");
+        assert_eq!(Registry::load(&path).unwrap().entries(), [entry(1, "3749", Verdict::Correct), entry(2, "100", Verdict::Wrong)]);
+
+        fs::write(&path, "7\t1\treal\t3749\n").unwrap();
+        assert!(Registry::load(&path).unwrap_err().contains("line 1: expected 5 tab separated fields"));
+    }
+
+    #[test]
+    fn test_known_wrong() {
+        let mut registry = Registry { path: PathBuf::new(), entries: Vec::new() };
+        registry.record(entry(1, "500", Verdict::TooHigh));
+        registry.record(entry(1, "100", Verdict::TooLow));
+        registry.record(entry(1, "250", Verdict::Wrong));
+
+        assert_eq!(registry.known_wrong(7, 1, "real", "250"), Some("250 was already rejected as wrong".to_string()));
+        assert_eq!(registry.known_wrong(7, 1, "real", "600"), Some("500 was already too high".to_string()));
+        assert_eq!(registry.known_wrong(7, 1, "real", "100"), Some("100 was already rejected as too_low".to_string()));
+        assert_eq!(registry.known_wrong(7, 1, "real", "99"), Some("100 was already too low".to_string()));
+        assert_eq!(registry.known_wrong(7, 1, "real", "300"), None);
+        assert_eq!(registry.known_wrong(7, 2, "real", "600"), None);
+        assert_eq!(registry.known_wrong(7, 1, "test", "600"), None);
+
+        registry.record(entry(1, "300", Verdict::Correct));
+        assert_eq!(registry.known_wrong(7, 1, "real", "300"), None);
+        assert!(registry.known_wrong(7, 1, "real", "301").is_some());
+        assert_eq!(registry.expected(7, 1, "real"), Some("300"));
+
+        registry.record(entry(1, "301", Verdict::Correct));
+        assert_eq!(registry.expected(7, 1, "real"), Some("301"));
+        assert_eq!(registry.entries().iter().filter(|e| e.verdict.is_correct()).count(), 1);
+        assert_eq!(registry.known_wrong(7, 1, "real", "600"), Some("the correct answer is already known to be 301".to_string()));
+    }
+
+    #[test]
+    fn test_check() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(input::DIR)).unwrap();
+        fs::write(input::alias_path(dir.path(), "test"), "1 2 3").unwrap();
+        fs::write(input::alias_path(dir.path(), "real"), "10 20 30").unwrap();
+
+        let mut registry = Registry { path: PathBuf::new(), entries: Vec::new() };
+        let mut add = |part, input: &str, answer: &str, verdict| {
+            registry.record(Entry { day: 3, part, input: input.to_string(), answer: answer.to_string(), verdict });
+        };
+        add(1, "test", "6", Verdict::Correct);
+        add(2, "test", "5", Verdict::Correct);
+        add(2, "test", "7", Verdict::TooHigh);
+        add(1, "real", "0", Verdict::Correct);
+
+        let sum = |part: u8, path: &Path| {
+            let numbers = fs::read_to_string(path).unwrap().split(' ').map(|n| n.parse::<u32>().unwrap()).collect::<Vec<_>>();
+            (numbers.iter().sum::<u32>() * part as u32).to_string()
+        };
+        assert_eq!(registry.check(3, dir.path(), sum), (2, vec!["part 2 on test: got 12, expected 5".to_string()]));
+        assert_eq!(registry.check(4, dir.path(), sum), (0, vec![]));
+    }
+}
@@ -0,0 +1,284 @@
+//! Printing answers.
+//!
+//! Days hand each part to [`Report::part`], which times it and prints the
+//! answer either as text for people or, with `--format json`, as one JSON
+//! object per line for scripts:
+//!
+//! ```text
+//! {"day":5,"part":1,"input":"/repo/day_5/files/test_input.txt","answer":"143","seconds":0.000412}
+//! ```
+//!
+//! Answers are always strings, as some do not fit in a JSON number. Only
+//! answers are reported this way; extra output asked for with flags such as
+//! `--render` is printed with [`extra!`](crate::extra), which sends it to
+//! stderr in JSON mode so stdout only ever holds answers.
+
+use crate::Options;
+use clap::ValueEnum;
+use std::{fmt, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    /// `Part 1 Solution: 143`
+    #[default]
+    Text,
+    /// One JSON object per answer.
+    Json,
+}
+
+/// Where the answers of one run of a day go.
+#[derive(Debug, Clone)]
+pub struct Report {
+    day: Option<u8>,
+    input: Option<PathBuf>,
+    format: Format,
+    /// Text line for a part and its answer, instead of `Part 1 Solution: 143`.
+    text: Option<fn(u8, &str) -> String>,
+}
+
+impl Report {
+    /// A report for the crate called `package`, e.g. `day_5`, reading `input`,
+    /// which is `None` for stdin.
+    pub fn new(package: &str, options: &Options, input: Option<&Path>) -> Report {
+        Report {
+            day: crate::day_number(package),
+            input: input.map(Path::to_path_buf),
+            format: options.format,
+            text: None,
+        }
+    }
+
+    /// Print text answers with `text` instead, for days that had their own wording.
+    pub fn with_text(self, text: fn(u8, &str) -> String) -> Report {
+        Report { text: Some(text), ..self }
+    }
+
+    /// Run `solve` and print its answer to `part`, labelled `label` in text.
+    pub fn part<T: fmt::Display>(&self, part: u8, label: &str, solve: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let answer = solve();
+        self.answer(part, label, &answer, start.elapsed());
+        answer
+    }
+
+    /// Whether answers are printed as JSON, so nothing else may go to stdout.
+    pub fn is_json(&self) -> bool {
+        self.format == Format::Json
+    }
+
+    /// Print an answer that took `elapsed` to find.
+    pub fn answer(&self, part: u8, label: &str, answer: &dyn fmt::Display, elapsed: Duration) {
+        println!("{}", self.line(part, label, &answer.to_string(), elapsed));
+    }
+
+    fn line(&self, part: u8, label: &str, answer: &str, elapsed: Duration) -> String {
+        match self.format {
+            Format::Text => match self.text {
+                Some(text) => text(part, answer),
+                None => format!("Part {part} {label}: {answer}"),
+            },
+            Format::Json => Answer {
+                day: self.day,
+                part,
+                input: self.input.as_ref().map(|path| path.to_string_lossy().into_owned()),
+                answer: answer.to_string(),
+                seconds: elapsed.as_secs_f64(),
+            }.to_json(),
+        }
+    }
+}
+
+/// One answer as printed with `--format json`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub day: Option<u8>,
+    pub part: u8,
+    pub input: Option<String>,
+    pub answer: String,
+    pub seconds: f64,
+}
+
+impl Answer {
+    pub fn to_json(&self) -> String {
+        let day = self.day.map_or("null".to_string(), |day| day.to_string());
+        let input = self.input.as_deref().map_or("null".to_string(), json_string);
+        format!(
+            "{{\"day\":{day},\"part\":{},\"input\":{input},\"answer\":{},\"seconds\":{}}}",
+            self.part, json_string(&self.answer), self.seconds,
+        )
+    }
+
+    /// Read a line printed with `--format json`, or `None` if it is not one.
+    pub fn from_json(line: &str) -> Option<Answer> {
+        let fields = json_fields(line)?;
+        let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value.clone());
+        Some(Answer {
+            day: field("day")?.map(|day| day.parse()).transpose().ok()?,
+            part: field("part")??.parse().ok()?,
+            input: field("input")?,
+            answer: field("answer")??,
+            seconds: field("seconds")??.parse().ok()?,
+        })
+    }
+}
+
+/// Build a report for the calling crate.
+///
+/// `aoc::report!(&cli.options, Some(&filename))`, with `None` when reading stdin.
+#[macro_export]
+macro_rules! report {
+    ($options:expr, $input:expr) => {
+        $crate::report::Report::new(env!("CARGO_PKG_NAME"), $options, $input)
+    };
+}
+
+/// Print a line of output other than an answer, like `println!`.
+///
+/// It goes to stdout as text, and to stderr when the report prints JSON.
+#[macro_export]
+macro_rules! extra {
+    ($report:expr, $($arg:tt)*) => {
+        if $report.is_json() {
+            eprintln!($($arg)*);
+        }
+        else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// The fields of a flat JSON object, with `null` as `None` and numbers as
+/// they are written. Nested objects and arrays are not supported.
+fn json_fields(line: &str) -> Option<Vec<(String, Option<String>)>> {
+    let mut chars = line.trim().chars().peekable();
+    let mut fields = Vec::new();
+    if chars.next()? != '{' {
+        return None;
+    }
+
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.next()? {
+            '}' if fields.is_empty() => break,
+            '"' => (),
+            _ => return None,
+        }
+        let key = read_string(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next()? != ':' {
+            return None;
+        }
+        skip_whitespace(&mut chars);
+
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            Some(read_string(&mut chars)?)
+        }
+        else {
+            let mut raw = String::new();
+            while let Some(c) = chars.next_if(|c| *c != ',' && *c != '}') {
+                raw.push(c);
+            }
+            match raw.trim() {
+                "null" => None,
+                "" => return None,
+                raw => Some(raw.to_string()),
+            }
+        };
+        fields.push((key, value));
+
+        skip_whitespace(&mut chars);
+        match chars.next()? {
+            ',' => (),
+            '}' => break,
+            _ => return None,
+        }
+    }
+    chars.next().is_none().then_some(fields)
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Read the rest of a JSON string whose opening quote has been taken.
+fn read_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => match chars.next()? {
+                'n' => s.push('\n'),
+                'r' => s.push('\r'),
+                't' => s.push('\t'),
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    s.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => s.push(c),
+            },
+            c => s.push(c),
+        }
+    }
+}
+
+/// `s` as a quoted JSON string.
+fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        let options = Options { format: Format::Json, ..Default::default() };
+        let report = Report::new("day_5", &options, Some(Path::new("files/test_input.txt")));
+        assert_eq!(
+            report.line(1, "Solution", "143", Duration::from_millis(250)),
+            r#"{"day":5,"part":1,"input":"files/test_input.txt","answer":"143","seconds":0.25}"#,
+        );
+
+        let report = Report::new("new", &options, None);
+        assert_eq!(
+            report.line(2, "Solution", "a \"b\"\n", Duration::ZERO),
+            r#"{"day":null,"part":2,"input":null,"answer":"a \"b\"\n","seconds":0}"#,
+        );
+
+        let report = Report::new("day_1", &Options::default(), None);
+        assert_eq!(report.line(2, "Distance", "31", Duration::ZERO), "Part 2 Distance: 31");
+        assert!(!report.is_json());
+
+        let report = report.with_text(|part, answer| format!("Safe count part {part} = {answer}"));
+        assert_eq!(report.line(1, "Safe count", "2", Duration::ZERO), "Safe count part 1 = 2");
+        let report = Report { format: Format::Json, ..report };
+        assert!(report.line(1, "Safe count", "2", Duration::ZERO).starts_with(r#"{"day":1,"part":1,"#));
+    }
+
+    #[test]
+    fn test_answer_from_json() {
+        let answer = Answer { day: Some(7), part: 2, input: Some("a \"b\"\\c\u{1}".to_string()), answer: "11387".to_string(), seconds: 0.5 };
+        assert_eq!(Answer::from_json(&answer.to_json()), Some(answer));
+
+        let answer = Answer::from_json(r#" { "day": null, "part": 1, "input": null, "answer": "x\u0041", "seconds": 1e-3 } "#).unwrap();
+        assert_eq!((answer.day, answer.input, answer.answer, answer.seconds), (None, None, "xA".to_string(), 0.001));
+
+        assert_eq!(Answer::from_json("Part 1 Solution: 143"), None);
+        assert_eq!(Answer::from_json(r#"{"day":5,"part":1}"#), None);
+        assert_eq!(Answer::from_json(r#"{"day":5,"part":1,"input":null,"answer":"1","seconds":0} trailing"#), None);
+    }
+}
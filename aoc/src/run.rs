@@ -0,0 +1,49 @@
+//! Running a day's binary and reading the answers it prints.
+
+use crate::{day_dir, report::Answer};
+use std::process::Command;
+
+/// The answers in a day's `--format json` output, in the order they are printed.
+///
+/// Only the first answer for each part is kept, and lines that are not answers
+/// are skipped, such as extra output asked for with other flags.
+pub fn parse_answers(output: &str) -> Vec<Answer> {
+    let mut answers: Vec<Answer> = Vec::new();
+    for answer in output.lines().filter_map(Answer::from_json) {
+        if answers.iter().all(|a| a.part != answer.part) {
+            answers.push(answer);
+        }
+    }
+    answers
+}
+
+/// Build and run day `day` in release mode on `input`, returning its answers.
+pub fn run_day(day: u8, input: &str) -> Result<Vec<Answer>, String> {
+    let output = Command::new(env!("CARGO"))
+        .args(["run", "--release", "--quiet", "--", "--format", "json", "--input", input])
+        .current_dir(day_dir(day))
+        .output()
+        .map_err(|e| format!("Unable to run day {day}: {e}"))?;
+
+    if !output.status.success() {
+        return Err(format!("Day {day} failed:\n{}", String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(parse_answers(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let output = concat!(
+            r#"{"day":7,"part":1,"input":"a","answer":"3749","seconds":0.1}"#, "\n",
+            "Solution with +*: 3749\n",
+            r#"{"day":7,"part":2,"input":"a","answer":"11387","seconds":0.2}"#, "\n",
+            r#"{"day":7,"part":2,"input":"a","answer":"0","seconds":0.2}"#, "\n",
+        );
+        let answers: Vec<(u8, String)> = parse_answers(output).into_iter().map(|a| (a.part, a.answer)).collect();
+        assert_eq!(answers, [(1, "3749".to_string()), (2, "11387".to_string())]);
+    }
+}
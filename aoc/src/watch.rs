@@ -0,0 +1,171 @@
+//! Re-running a day whenever its code or inputs change.
+//!
+//! `aoc watch` watches the day's `src/` and `files/` directories and reruns the
+//! day in release mode after every change, comparing each answer with the one
+//! from the run before and with the expected answer in the registry, or in
+//! the answers saved next to a generated input.
+
+use crate::{day_dir, generate, input, registry::Registry, report::Answer, run};
+use notify::{event::{AccessKind, AccessMode, ModifyKind}, Event, EventKind, RecursiveMode, Watcher};
+use std::{path::{Path, PathBuf}, sync::mpsc::{self, Receiver}, time::Duration};
+
+/// How long to wait for a burst of changes to settle, as editors often write a file several times when saving.
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// One line per part describing `answers` against the previous run and the expected answers.
+pub fn summary(answers: &[Answer], previous: &[Answer], expected: impl Fn(u8) -> Option<String>) -> Vec<String> {
+    let mut parts: Vec<u8> = answers.iter().chain(previous).map(|a| a.part).collect();
+    parts.sort();
+    parts.dedup();
+
+    parts.into_iter().map(|part| {
+        let answer = answers.iter().find(|a| a.part == part);
+        let before = previous.iter().find(|a| a.part == part).map(|a| a.answer.as_str());
+        let Some(answer) = answer else {
+            return format!("Part {part}: no answer, was {}", before.unwrap_or_default());
+        };
+
+        let mut line = format!("Part {part}: {} in {:.1?}", answer.answer, Duration::from_secs_f64(answer.seconds));
+        match before {
+            Some(before) if before == answer.answer => line.push_str(", unchanged"),
+            Some(before) => line.push_str(&format!(", changed from {before}")),
+            None if !previous.is_empty() => line.push_str(", new"),
+            None => (),
+        }
+        match expected(part) {
+            Some(expected) if expected == answer.answer => line.push_str(", correct"),
+            Some(expected) => line.push_str(&format!(", WRONG, expected {expected}")),
+            None => (),
+        }
+        line
+    }).collect()
+}
+
+/// Run day `day` on `input` now and again after every change, until the watcher fails.
+pub fn watch(day: u8, input: &str) -> Result<(), String> {
+    let dir = day_dir(day);
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| format!("Unable to watch for changes: {e}"))?;
+    for watched in [dir.join("src"), dir.join(input::DIR)] {
+        watcher.watch(&watched, RecursiveMode::Recursive).map_err(|e| format!("{}: {e}", watched.display()))?;
+    }
+
+    let mut previous = Vec::new();
+    loop {
+        println!("Running day {day} on {input}");
+        match run::run_day(day, input) {
+            Ok(answers) => {
+                let registry = load_registry(&registry_path(&dir, input));
+                let expected = |part| registry.as_ref()?.expected(day, part, input).map(str::to_string);
+                for line in summary(&answers, &previous, expected) {
+                    println!("  {line}");
+                }
+                previous = answers;
+            }
+            Err(e) => eprintln!("{e}"),
+        }
+        println!("Waiting for changes to {}/{{src,{}}}", dir.display(), input::DIR);
+        wait_for_change(&receiver)?;
+    }
+}
+
+/// Where the expected answers for `input` are: next to it for generated inputs,
+/// otherwise in `answers.tsv`.
+fn registry_path(dir: &Path, input: &str) -> PathBuf {
+    let generated = generate::answers_path(dir, input);
+    match generated.is_file() {
+        true => generated,
+        false => Registry::default_path(),
+    }
+}
+
+/// The registry at `path`, or `None` after saying why it could not be read,
+/// so a bad line in it does not end the session.
+fn load_registry(path: &Path) -> Option<Registry> {
+    Registry::load(path)
+        .map_err(|e| eprintln!("Not checking expected answers: {e}"))
+        .ok()
+}
+
+/// Block until something is created, changed or removed, then let the burst settle.
+fn wait_for_change(receiver: &Receiver<notify::Result<Event>>) -> Result<(), String> {
+    loop {
+        match receiver.recv() {
+            Ok(Ok(event)) if is_change(&event.kind) => break,
+            Ok(Ok(_)) => continue,
+            Ok(Err(e)) => return Err(format!("Watching for changes failed: {e}")),
+            Err(_) => return Err("The watcher stopped.".to_string()),
+        }
+    }
+    while receiver.recv_timeout(SETTLE).is_ok() {}
+    Ok(())
+}
+
+fn is_change(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Create(_) | EventKind::Remove(_) => true,
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Modify(ModifyKind::Metadata(_)) => false,
+        EventKind::Modify(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, MetadataKind};
+    use std::fs;
+
+    fn answer(part: u8, answer: &str) -> Answer {
+        Answer { day: Some(5), part, input: None, answer: answer.to_string(), seconds: 0.0015 }
+    }
+
+    #[test]
+    fn test_summary() {
+        let expected = |part| (part == 1).then(|| "143".to_string());
+
+        assert_eq!(summary(&[answer(1, "143"), answer(2, "120")], &[], expected), [
+            "Part 1: 143 in 1.5ms, correct",
+            "Part 2: 120 in 1.5ms",
+        ]);
+        assert_eq!(summary(&[answer(1, "142"), answer(2, "123")], &[answer(1, "142")], expected), [
+            "Part 1: 142 in 1.5ms, unchanged, WRONG, expected 143",
+            "Part 2: 123 in 1.5ms, new",
+        ]);
+        assert_eq!(summary(&[answer(1, "143")], &[answer(1, "142"), answer(2, "123")], expected), [
+            "Part 1: 143 in 1.5ms, changed from 142, correct",
+            "Part 2: no answer, was 123",
+        ]);
+    }
+
+    #[test]
+    fn test_load_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.tsv");
+        fs::write(&path, "5\t1\ttest\t143\tcorrect\n").unwrap();
+        assert_eq!(load_registry(&path).unwrap().expected(5, 1, "test"), Some("143"));
+
+        fs::write(&path, "5\t1\ttest\t143\n").unwrap();
+        assert!(load_registry(&path).is_none());
+    }
+
+    #[test]
+    fn test_registry_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let generated = generate::Generated { input: "1\n".to_string(), answers: ["1".to_string(), "2".to_string()] };
+        generate::save(dir.path(), "day_5", 10, 7, &generated).unwrap();
+        assert_eq!(registry_path(dir.path(), "gen-10-7"), dir.path().join("files/gen-10-7_answers.tsv"));
+        assert_eq!(registry_path(dir.path(), "gen-10-8"), Registry::default_path());
+        assert_eq!(registry_path(dir.path(), "test"), Registry::default_path());
+    }
+
+    #[test]
+    fn test_is_change() {
+        assert!(is_change(&EventKind::Create(CreateKind::File)));
+        assert!(is_change(&EventKind::Modify(ModifyKind::Data(DataChange::Content))));
+        assert!(!is_change(&EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime))));
+        assert!(is_change(&EventKind::Access(AccessKind::Close(AccessMode::Write))));
+        assert!(!is_change(&EventKind::Access(AccessKind::Read)));
+    }
+}
@@ -1,61 +1,351 @@
-use std::io;
+use std::{io::BufRead, time::Instant};
+use clap::Parser;
 use regex::Regex;
 use itertools::izip;
-use std::collections::HashMap;
+use std::{collections::{BTreeMap, HashMap}, fmt};
+use tracing::{event, instrument, Level};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Count values while reading instead of keeping both lists, for very large inputs.
+    #[arg(short, long)]
+    streaming: bool,
+
+    /// Largest value in either list, lets streaming mode count into a flat table.
+    #[arg(short, long, requires = "streaming")]
+    max_value: Option<u64>,
+
+    /// The two columns to compare, numbered from 1.
+    #[arg(short, long, value_name = "LEFT,RIGHT", value_parser = parse_columns, default_value = "1,2")]
+    columns: (usize, usize),
+
+    /// Print min, max, median, duplicates and the most common values of every column.
+    #[arg(long, conflicts_with = "streaming")]
+    stats: bool,
+
+    #[command(flatten)]
+    options: aoc::Options,
+}
+
+/// Parse a `LEFT,RIGHT` pair of 1-based column numbers into 0-based indices.
+fn parse_columns(s: &str) -> Result<(usize, usize), String> {
+    let columns: Vec<usize> = s.split(',')
+        .map(|c| c.trim().parse().map_err(|_| format!("invalid column '{c}'")))
+        .collect::<Result<_, _>>()?;
+    match columns[..] {
+        [left, right] if left > 0 && right > 0 => Ok((left - 1, right - 1)),
+        _ => Err("expected two column numbers starting from 1, e.g. 1,2".to_string()),
+    }
+}
 
 fn main() {
-    // Read in two lists of numbers.
-    let mut v1: Vec<i32> = Vec::new();
-    let mut v2: Vec<i32> = Vec::new();
-    let mut h2: HashMap<i32, i32> = HashMap::new();
-    let re = Regex::new(r"^(\d+)\s+(\d+)$").unwrap();
 
-    loop {
-        let mut read_string = String::new();
+    let cli = Args::parse();
+    aoc::init(&cli.options);
 
-        io::stdin()
-            .read_line(&mut read_string).expect("io read error");
+    let filename = cli.options.input().map(|input| aoc::input!(input).unwrap_or_else(|e| aoc::fail(e)));
+    let report = aoc::report!(&cli.options, filename.as_deref());
+    let reader = aoc::input::open(filename.as_deref());
 
-        let Some(cap,) = re.captures(&read_string.trim()) else {
-            break;
-        };
+    if cli.streaming {
+        // Both parts are counted in the same pass, so they share its time.
+        let start = Instant::now();
+        let (distance, similarity) = streaming(reader, cli.max_value, cli.columns);
+        report.answer(1, "Distance", &distance, start.elapsed());
+        report.answer(2, "Distance", &similarity, start.elapsed());
+        return;
+    }
+
+    // Read in the lists of numbers, one per column.
+    let columns = read_columns(reader);
 
-        let number1 = &cap[1];
-        let number2 = &cap[2];
+    let (left, right) = cli.columns;
+    let (Some(v1), Some(v2)) = (columns.get(left), columns.get(right)) else {
+        panic!("Input has {} columns, cannot compare columns {} and {}.", columns.len(), left + 1, right + 1);
+    };
 
-        let number1:i32 = number1.parse().unwrap();
-        let number2:i32 = number2.parse().unwrap();
+    report.part(1, "Distance", || distance(v1, v2));
 
-        v1.push(number1);
-        v2.push(number2);
+    report.part(2, "Distance", || similarity(v1, v2));
 
-        let count = h2.entry(number2).or_insert(0);
-        *count += 1; 
+    if cli.stats {
+        for (i, column) in columns.iter().enumerate() {
+            aoc::extra!(report, "Column {}: {}", i + 1, ColumnStats::new(column));
+        }
     }
 
+}
+
+/// Read whitespace separated columns of numbers until the first line that is not one.
+#[instrument(name = "parse", skip_all)]
+fn read_columns(reader: impl BufRead) -> Vec<Vec<i64>> {
+    let re = Regex::new(r"^\d+(\s+\d+)*$").unwrap();
+    let mut columns: Vec<Vec<i64>> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.expect("io read error");
+        if !re.is_match(line.trim()) {
+            break;
+        }
+
+        let numbers: Vec<i64> = line.split_whitespace().map(|n| n.parse().unwrap()).collect();
+        if columns.is_empty() {
+            columns.resize(numbers.len(), Vec::new());
+        }
+        assert!(numbers.len() == columns.len(), "Line {line:?} has {} columns, expected {}.", numbers.len(), columns.len());
+
+        for (column, number) in columns.iter_mut().zip(numbers) {
+            column.push(number);
+        }
+    }
+
+    event!(Level::INFO, "Read {} columns of {} numbers", columns.len(), columns.first().map_or(0, Vec::len));
+    columns
+}
+
+/// Sum of the distances between the two lists once both are sorted (Part 1).
+#[instrument(name = "part_1", skip_all)]
+fn distance(v1: &[i64], v2: &[i64]) -> i64 {
+    let mut v1 = v1.to_vec();
+    let mut v2 = v2.to_vec();
+
     // Sort both lists
     v1.sort();
     v2.sort();
 
-    // Compare distance of both numbers (Part 1)
-    let mut distance: i32 = 0;
+    // Compare distance of both numbers
+    let mut distance: i64 = 0;
     for (x, y) in izip!(&v1, &v2) {
-        let d = (x - y).abs();
-        // println!("dist({x},{y})={d}");
-        distance += d;
+        distance += (x - y).abs();
+    }
+    distance
+}
+
+/// Sum of each left value times how often it appears in the right list (Part 2).
+#[instrument(name = "part_2", skip_all)]
+fn similarity(v1: &[i64], v2: &[i64]) -> i64 {
+    let mut h2: HashMap<i64, i64> = HashMap::new();
+    for number in v2 {
+        *h2.entry(*number).or_insert(0) += 1;
+    }
+
+    v1.iter().map(|x| x * h2.get(x).unwrap_or(&0)).sum()
+}
+
+/// Summary statistics for one column.
+#[derive(Debug, PartialEq)]
+struct ColumnStats {
+    count: usize,
+    min: i64,
+    max: i64,
+    median: f64,
+    /// Number of distinct values that appear more than once.
+    duplicates: usize,
+    /// Up to three of the most frequent values with their counts, most frequent first.
+    most_common: Vec<(i64, usize)>,
+}
+
+impl ColumnStats {
+    fn new(values: &[i64]) -> ColumnStats {
+        let mut sorted = values.to_vec();
+        sorted.sort();
+
+        let mid = sorted.len() / 2;
+        let median = match sorted.len() {
+            0 => f64::NAN,
+            n if n % 2 == 0 => (sorted[mid - 1] + sorted[mid]) as f64 / 2.0,
+            _ => sorted[mid] as f64,
+        };
+
+        let mut counts: Vec<(i64, usize)> = sorted.chunk_by(|a, b| a == b).map(|run| (run[0], run.len())).collect();
+        let duplicates = counts.iter().filter(|(_, count)| *count > 1).count();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts.truncate(3);
+
+        ColumnStats {
+            count: sorted.len(),
+            min: sorted.first().copied().unwrap_or_default(),
+            max: sorted.last().copied().unwrap_or_default(),
+            median,
+            duplicates,
+            most_common: counts,
+        }
+    }
+}
+
+impl fmt::Display for ColumnStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let most_common: Vec<String> = self.most_common.iter().map(|(value, count)| format!("{value} (x{count})")).collect();
+        write!(f, "{} values, min {}, max {}, median {}, {} duplicated values, most common {}",
+            self.count, self.min, self.max, self.median, self.duplicates, most_common.join(", "))
     }
+}
 
-    println!("Part 1 Distance: {distance}");
+/// How often each value appears in one list.
+enum Counts {
+    /// Indexed by value, for inputs with a known bounded range.
+    Dense(Vec<u64>),
+    /// Keyed by value, for inputs with an unknown range.
+    Sparse(BTreeMap<u64, u64>),
+}
 
-    // Calculate Part 2 Distance
-    let mut distance_p2: i32 = 0;
-    for x in v1 {
-        let count = h2.entry(x).or_insert(0);
-        let d = x * *count;
-        // println!("dist({x},hash{x}={count})={d}");
-        distance_p2 += d;
+impl Counts {
+    fn new(max_value: Option<u64>) -> Counts {
+        match max_value {
+            Some(max) => Counts::Dense(vec![0; max as usize + 1]),
+            None => Counts::Sparse(BTreeMap::new()),
+        }
     }
 
-    println!("Part 2 Distance: {distance_p2}");
+    fn add(&mut self, value: u64) {
+        match self {
+            Counts::Dense(counts) => {
+                let Some(count) = counts.get_mut(value as usize) else {
+                    panic!("Value {value} is larger than --max-value {}.", counts.len() - 1);
+                };
+                *count += 1;
+            }
+            Counts::Sparse(counts) => *counts.entry(value).or_insert(0) += 1,
+        }
+    }
 
-}
\ No newline at end of file
+    fn get(&self, value: u64) -> u64 {
+        match self {
+            Counts::Dense(counts) => counts.get(value as usize).copied().unwrap_or(0),
+            Counts::Sparse(counts) => counts.get(&value).copied().unwrap_or(0),
+        }
+    }
+
+    /// Each value that appears, in ascending order, with how often it appears.
+    fn iter(&self) -> Box<dyn Iterator<Item = (u64, u64)> + '_> {
+        match self {
+            Counts::Dense(counts) => Box::new(counts.iter().enumerate().filter(|(_, c)| **c > 0).map(|(v, c)| (v as u64, *c))),
+            Counts::Sparse(counts) => Box::new(counts.iter().map(|(v, c)| (*v, *c))),
+        }
+    }
+}
+
+/// Compute the distance and similarity without keeping either list in memory.
+///
+/// Both lists are counting sorted as they are read, so memory grows with the
+/// number of distinct values rather than the number of pairs. Reading stops at
+/// the first line that is not all numbers.
+#[instrument(skip(reader))]
+fn streaming(reader: impl BufRead, max_value: Option<u64>, columns: (usize, usize)) -> (u64, u64) {
+    let mut left = Counts::new(max_value);
+    let mut right = Counts::new(max_value);
+
+    for line in reader.lines() {
+        let line = line.expect("io read error");
+        let Ok(numbers) = line.split_whitespace().map(|n| n.parse::<u64>()).collect::<Result<Vec<_>, _>>() else {
+            break;
+        };
+        let (Some(x), Some(y)) = (numbers.get(columns.0).copied(), numbers.get(columns.1).copied()) else {
+            break;
+        };
+        left.add(x);
+        right.add(y);
+    }
+
+    // Walk both sorted lists together, pairing off the smallest remaining values.
+    let mut distance: u64 = 0;
+    let mut right_iter = right.iter();
+    let mut current = right_iter.next();
+    for (x, mut x_count) in left.iter() {
+        while x_count > 0 {
+            let Some((y, y_count)) = current.as_mut() else {
+                panic!("The left list is longer than the right list.");
+            };
+            let paired = x_count.min(*y_count);
+            distance += paired * x.abs_diff(*y);
+            x_count -= paired;
+            *y_count -= paired;
+            if *y_count == 0 {
+                current = right_iter.next();
+            }
+        }
+    }
+
+    let similarity = left.iter().map(|(x, count)| x * count * right.get(x)).sum();
+
+    (distance, similarity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_streaming_test_input() {
+        let contents = fs::read_to_string(aoc::input!("test").unwrap()).expect("Unable to read file.");
+        for max_value in [None, Some(9)] {
+            assert_eq!(streaming(contents.as_bytes(), max_value, (0, 1)), (11, 31));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
+    fn test_streaming_real_input() {
+        let contents = fs::read_to_string(aoc::input!("real").unwrap()).expect("Unable to read file.");
+        for max_value in [None, Some(99999)] {
+            assert_eq!(streaming(contents.as_bytes(), max_value, (0, 1)), (2164381, 20719933));
+        }
+    }
+
+    #[test]
+    fn test_streaming_large_values() {
+        let big = u32::MAX as u64 * 4;
+        let contents = format!("{big}   1\n1   {big}\n{big}   {big}\nend");
+        assert_eq!(streaming(contents.as_bytes(), None, (0, 1)), (0, big * 2 * 2 + 1));
+    }
+
+    #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
+    fn test_two_columns() {
+        let contents = fs::read_to_string(aoc::input!("real").unwrap()).expect("Unable to read file.");
+        let columns = read_columns(contents.as_bytes());
+        assert_eq!(columns.len(), 2);
+        assert_eq!(distance(&columns[0], &columns[1]), 2164381);
+        assert_eq!(similarity(&columns[0], &columns[1]), 20719933);
+    }
+
+    #[test]
+    fn test_multiple_columns() {
+        let contents = "3   4   3\n4   3   4\n2   5   2\n1   3   1\n3   9   3\n3   3   3\n";
+        let columns = read_columns(contents.as_bytes());
+        assert_eq!(columns.len(), 3);
+        assert_eq!((distance(&columns[0], &columns[1]), similarity(&columns[0], &columns[1])), (11, 31));
+        assert_eq!((distance(&columns[2], &columns[1]), similarity(&columns[2], &columns[1])), (11, 31));
+        assert_eq!((distance(&columns[0], &columns[2]), similarity(&columns[0], &columns[2])), (0, 3 * 3 * 3 + 4 + 2 + 1));
+        assert_eq!(streaming(contents.as_bytes(), None, (2, 1)), (11, 31));
+    }
+
+    #[test]
+    fn test_column_stats() {
+        let stats = ColumnStats::new(&[3, 4, 2, 1, 3, 3]);
+        assert_eq!(stats, ColumnStats{count: 6, min: 1, max: 4, median: 3.0, duplicates: 1, most_common: vec![(3, 3), (1, 1), (2, 1)]});
+
+        let stats = ColumnStats::new(&[4, 3, 5, 3, 9, 3, 4]);
+        assert_eq!(stats.median, 4.0);
+        assert_eq!(stats.duplicates, 2);
+        assert_eq!(stats.to_string(), "7 values, min 3, max 9, median 4, 2 duplicated values, most common 3 (x3), 4 (x2), 5 (x1)");
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(parse_columns("1,2"), Ok((0, 1)));
+        assert_eq!(parse_columns("3, 1"), Ok((2, 0)));
+        assert!(parse_columns("0,1").is_err());
+        assert!(parse_columns("1").is_err());
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| {
+            let (distance, similarity) = streaming(aoc::input::open(Some(path)), None, (0, 1));
+            [distance, similarity][part as usize - 1].to_string()
+        });
+    }
+}
@@ -1,109 +1,390 @@
-use std::{cmp::Ordering, io};
+use std::{cmp::Ordering, fmt, io::BufRead};
+use clap::Parser;
 use itertools::Itertools;
-use tracing::{event, Level};
-use tracing_subscriber;
+use tracing::{event, instrument, Level};
 
-#[derive(Debug)]
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Smallest change between neighbouring levels.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    min_step: u32,
+
+    /// Largest change between neighbouring levels.
+    #[arg(long, default_value_t = 3)]
+    max_step: u32,
+
+    /// Allow neighbouring levels to be equal without breaking the direction.
+    #[arg(long)]
+    allow_equal: bool,
+
+    /// Number of bad levels the Problem Dampener may remove in part 2.
+    #[arg(short, long, default_value_t = 1)]
+    tolerance: usize,
+
+    #[command(flatten)]
+    options: aoc::Options,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Mode {
     Increasing,
     Decreasing,
     Unset,
 }
 
+/// What counts as a safe step between two neighbouring levels.
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    min_step: u32,
+    max_step: u32,
+    allow_equal: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules { min_step: 1, max_step: 3, allow_equal: false }
+    }
+}
+
+/// The rule a pair of neighbouring levels breaks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rule {
+    Equal,
+    TooSmall { step: u32, min: u32 },
+    TooLarge { step: u32, max: u32 },
+    Direction { expected: Mode },
+}
+
+/// The first pair of levels in a report that breaks a rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Violation {
+    /// Index of the first level of the pair.
+    index: usize,
+    values: (i32, i32),
+    rule: Rule,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y) = self.values;
+        write!(f, "levels {} and {} ({x} -> {y}) ", self.index, self.index + 1)?;
+        match self.rule {
+            Rule::Equal => write!(f, "are equal"),
+            Rule::TooSmall { step, min } => write!(f, "change by {step}, less than the minimum step {min}"),
+            Rule::TooLarge { step, max } => write!(f, "change by {step}, more than the maximum step {max}"),
+            Rule::Direction { expected } => write!(f, "break the {} direction", format!("{expected:?}").to_lowercase()),
+        }
+    }
+}
+
 fn main() {
 
-    // construct a subscriber that prints formatted traces to stdout
-    let subscriber = tracing_subscriber::FmtSubscriber::builder().with_max_level(Level::ERROR).finish();
-    // use that subscriber to process traces emitted after this point
-    tracing::subscriber::set_global_default(subscriber).expect("Error registering tracer.");
-    
+    let cli = Args::parse();
+    aoc::init(&cli.options);
+
+    if cli.min_step > cli.max_step {
+        aoc::fail(format!("--min-step {} is larger than --max-step {}.", cli.min_step, cli.max_step));
+    }
+    let rules = Rules { min_step: cli.min_step, max_step: cli.max_step, allow_equal: cli.allow_equal };
+
+    let filename = cli.options.input().map(|input| aoc::input!(input).unwrap_or_else(|e| aoc::fail(e)));
+    let report = aoc::report!(&cli.options, filename.as_deref())
+        .with_text(|part, answer| format!("Safe count part {part} = {answer}"));
+
+    let reports = parse_reports(aoc::input::open(filename.as_deref()));
 
-    // Read in matrix of numbers.
-    let reports: Vec<Vec<i32>> = io::stdin().lines()
-        .map(|l| l.unwrap().trim().split_whitespace()
+    report.part(1, "Safe count", || part_1(&reports, &rules));
+
+    report.part(2, "Safe count", || part_2(&reports, cli.tolerance, &rules));
+}
+
+/// Read in matrix of numbers, one report per line.
+#[instrument(skip_all)]
+fn parse_reports(reader: impl BufRead) -> Vec<Vec<i32>> {
+    let reports: Vec<Vec<i32>> = reader.lines()
+        .map(|l| l.unwrap().split_whitespace()
         .map(|number| number.parse().unwrap()).collect())
         .collect();
 
-    let mut safe_count_pt1 = 0;
-    let mut safe_count_pt2 = 0;
+    event!(Level::INFO, "Read {} reports", reports.len());
+    reports
+}
+
+/// Count the safe reports, explaining why each unsafe one fails.
+#[instrument(skip_all)]
+fn part_1(reports: &[Vec<i32>], rules: &Rules) -> usize {
+    reports.iter().enumerate().filter(|(number, report)| {
+        match check(report, rules) {
+            Ok(()) => {
+                event!(Level::INFO, "Report {number} {report:?} is safe");
+                true
+            }
+            Err(violation) => {
+                event!(Level::INFO, "Report {number} {report:?} is unsafe: {violation}");
+                false
+            }
+        }
+    }).count()
+}
+
+/// Count the reports that are safe after removing up to `tolerance` bad levels.
+#[instrument(skip(reports, rules))]
+fn part_2(reports: &[Vec<i32>], tolerance: usize, rules: &Rules) -> usize {
+    reports.iter().enumerate().filter(|(number, report)| {
+        let safe = safe_with_removals(report, tolerance, rules);
+        if safe {
+            event!(Level::INFO, "Report {number} is safe with the Problem Dampener");
+        }
+        safe
+    }).count()
+}
 
-    // Determine if the report is safe or unsafe
-    for report in &reports {
+/// The rule broken by stepping from `x` to `y` in `mode`, if any.
+fn step_violation(x: i32, y: i32, mode: Mode, rules: &Rules) -> Option<Rule> {
+    let delta = y - x;
+    let step = delta.unsigned_abs();
+    let expected = match mode {
+        Mode::Increasing => Ordering::Greater,
+        Mode::Decreasing => Ordering::Less,
+        Mode::Unset => panic!("Unexpected state."),
+    };
 
-        event!{Level::INFO, "Report {report:?}"};
+    match delta.cmp(&0) {
+        Ordering::Equal if rules.allow_equal => None,
+        Ordering::Equal => Some(Rule::Equal),
+        direction if direction != expected => Some(Rule::Direction { expected: mode }),
+        _ if step < rules.min_step => Some(Rule::TooSmall { step, min: rules.min_step }),
+        _ if step > rules.max_step => Some(Rule::TooLarge { step, max: rules.max_step }),
+        _ => None,
+    }
+}
 
-        let mut safe = true;
-        let mut mode = Mode::Unset;
+/// Whether stepping from `x` to `y` keeps the report moving in `mode` within the rules.
+fn valid_step(x: i32, y: i32, mode: Mode, rules: &Rules) -> bool {
+    step_violation(x, y, mode, rules).is_none()
+}
 
-        for (x, y) in report.into_iter().tuple_windows() {
-            let delta = x - y;
+/// Find the first pair of levels that breaks the rules.
+///
+/// The direction is set by the first pair of levels that differ.
+fn check(report: &[i32], rules: &Rules) -> Result<(), Violation> {
+    let mut mode = Mode::Unset;
 
-            event!{Level::INFO, "x {x} y {y}"};
-            if let Mode::Unset = mode {
-                match x.cmp(&y) {
-                    Ordering::Equal => { safe = false; break } ,
-                    Ordering::Less => mode = Mode::Increasing,
-                    Ordering::Greater => mode = Mode::Decreasing,
-                }
-            }
+    for (index, (x, y)) in report.iter().tuple_windows().enumerate() {
 
-            event!(Level::INFO, "Mode = {mode:?}, delta = {delta}");
-            
-            match mode {
-                Mode::Increasing => {if !(delta <= -1 && delta >= -3) {safe = false; break} },
-                Mode::Decreasing => {if !(delta >= 1 && delta <= 3) {safe = false; break} },
-                Mode::Unset => {panic!("Unexpected state.")}
+        event!{Level::DEBUG, "x {x} y {y}"};
+        if let Mode::Unset = mode {
+            match x.cmp(y) {
+                Ordering::Equal if rules.allow_equal => continue,
+                Ordering::Equal => return Err(Violation { index, values: (*x, *y), rule: Rule::Equal }),
+                Ordering::Less => mode = Mode::Increasing,
+                Ordering::Greater => mode = Mode::Decreasing,
             }
         }
 
-        if safe == true {
-            event!(Level::INFO, "Report {report:?} is safe");
-            safe_count_pt1 += 1;
+        event!(Level::DEBUG, "Mode = {mode:?}, delta = {}", y - x);
+
+        if let Some(rule) = step_violation(*x, *y, mode, rules) {
+            return Err(Violation { index, values: (*x, *y), rule });
         }
     }
+    Ok(())
+}
 
-    // Determine if the report is safe or unsafe
-    for report in &reports {
+/// Whether the levels all move in one direction within the rules.
+fn is_safe(report: &[i32], rules: &Rules) -> bool {
+    check(report, rules).is_ok()
+}
 
-        event!{Level::INFO, "Report {report:?}"};
+/// Whether the report is safe after removing at most `k` levels.
+fn safe_with_removals(report: &[i32], k: usize, rules: &Rules) -> bool {
+    match k {
+        0 => is_safe(report, rules),
+        1 => is_safe_dampened(report, rules),
+        _ => [Mode::Increasing, Mode::Decreasing].into_iter().any(|mode| min_removals(report, mode, k, rules).is_some()),
+    }
+}
 
-        // For each removal point
-        for i in 0..report.len() {
-            let mut report_with_drop = report.to_vec();
-            report_with_drop.remove(i);
+/// Whether removing at most one level makes the report safe, in O(n).
+///
+/// For each direction, the first bad step must involve one of its two levels,
+/// so only those two removals need checking.
+fn is_safe_dampened(report: &[i32], rules: &Rules) -> bool {
+    [Mode::Increasing, Mode::Decreasing].into_iter().any(|mode| {
+        match report.iter().tuple_windows().position(|(x, y)| !valid_step(*x, *y, mode, rules)) {
+            None => true,
+            Some(i) => valid_without(report, i, mode, rules) || valid_without(report, i + 1, mode, rules),
+        }
+    })
+}
 
-            let mut safe = true;
-            let mut mode = Mode::Unset;
-
-            for (x, y) in report_with_drop.into_iter().tuple_windows() {
-                let delta = x - y;
-
-                event!{Level::INFO, "x {x} y {y}"};
-                if let Mode::Unset = mode {
-                    match x.cmp(&y) {
-                        Ordering::Equal => { safe = false; break } ,
-                        Ordering::Less => mode = Mode::Increasing,
-                        Ordering::Greater => mode = Mode::Decreasing,
-                    }
-                }
-
-                event!(Level::INFO, "Mode = {mode:?}, delta = {delta}");
-                
-                match mode {
-                    Mode::Increasing => {if !(delta <= -1 && delta >= -3) {safe = false; break} },
-                    Mode::Decreasing => {if !(delta >= 1 && delta <= 3) {safe = false; break} },
-                    Mode::Unset => {panic!("Unexpected state.")}
-                }
-            }
+/// Whether every step is valid in `mode` once the level at `skip` is removed.
+fn valid_without(report: &[i32], skip: usize, mode: Mode, rules: &Rules) -> bool {
+    report.iter().enumerate()
+        .filter(|(i, _)| *i != skip)
+        .tuple_windows()
+        .all(|((_, x), (_, y))| valid_step(*x, *y, mode, rules))
+}
 
-            if safe == true {
-                event!(Level::INFO, "Report {report:?} is safe");
-                safe_count_pt2 += 1;
-                break;
+/// The fewest levels to remove so the report is safe in `mode`, if it is at most `k`.
+///
+/// `kept[i]` is the longest safe run of kept levels ending at level `i`. At
+/// most `k` levels can be skipped before `i`, so only the previous `k + 1`
+/// levels can come before it, giving O(n * k).
+fn min_removals(report: &[i32], mode: Mode, k: usize, rules: &Rules) -> Option<usize> {
+    if report.len() <= 1 {
+        return Some(0);
+    }
+
+    let mut kept = vec![0; report.len()];
+    let mut best = None;
+    for i in 0..report.len() {
+        // Everything before `i` is removed if it starts the run.
+        kept[i] = if i <= k { 1 } else { 0 };
+        for j in i.saturating_sub(k + 1)..i {
+            if kept[j] > 0 && valid_step(report[j], report[i], mode, rules) {
+                kept[i] = kept[i].max(kept[j] + 1);
+            }
+        }
+        // Everything after `i` is removed if it ends the run.
+        if kept[i] > 0 {
+            let removals = report.len() - kept[i];
+            if removals <= k {
+                best = Some(best.map_or(removals, |b: usize| b.min(removals)));
             }
         }
     }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::differential::Variants;
+    use proptest::prelude::*;
+
+    fn read_reports(input: &str) -> Vec<Vec<i32>> {
+        parse_reports(aoc::input::open(Some(&aoc::input!(input).unwrap())))
+    }
+
+    /// The original Problem Dampener: try removing every level in turn.
+    fn safe_with_removals_brute(report: &[i32], k: usize, rules: &Rules) -> bool {
+        if is_safe(report, rules) {
+            return true;
+        }
+        if k == 0 {
+            return false;
+        }
+        (0..report.len()).any(|i| {
+            let mut report_with_drop = report.to_vec();
+            report_with_drop.remove(i);
+            safe_with_removals_brute(&report_with_drop, k - 1, rules)
+        })
+    }
+
+    #[test]
+    fn test_test_input() {
+        let rules = Rules::default();
+        let reports = read_reports("test");
+        assert_eq!(part_1(&reports, &rules), 2);
+        assert_eq!(part_2(&reports, 1, &rules), 4);
+    }
+
+    #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
+    fn test_real_input() {
+        let rules = Rules::default();
+        let reports = read_reports("real");
+        assert_eq!(part_1(&reports, &rules), 230);
+        assert_eq!(part_2(&reports, 1, &rules), 301);
+    }
+
+    #[test]
+    fn test_min_removals() {
+        let rules = Rules::default();
+        assert_eq!(min_removals(&[1, 3, 2, 4, 5], Mode::Increasing, 1, &rules), Some(1));
+        assert_eq!(min_removals(&[1, 3, 2, 4, 5], Mode::Decreasing, 1, &rules), None);
+        assert_eq!(min_removals(&[9, 1, 2, 3, 9, 4], Mode::Increasing, 2, &rules), Some(2));
+        assert_eq!(min_removals(&[9, 1, 2, 3, 9, 4], Mode::Increasing, 1, &rules), None);
+        assert_eq!(min_removals(&[5], Mode::Decreasing, 0, &rules), Some(0));
+    }
+
+    #[test]
+    fn test_violations() {
+        let rules = Rules::default();
+        assert_eq!(check(&[7, 6, 4, 2, 1], &rules), Ok(()));
+        assert_eq!(check(&[1, 2, 7, 8, 9], &rules), Err(Violation { index: 1, values: (2, 7), rule: Rule::TooLarge { step: 5, max: 3 } }));
+        assert_eq!(check(&[1, 3, 2, 4, 5], &rules), Err(Violation { index: 1, values: (3, 2), rule: Rule::Direction { expected: Mode::Increasing } }));
+        assert_eq!(check(&[8, 6, 4, 4, 1], &rules), Err(Violation { index: 2, values: (4, 4), rule: Rule::Equal }));
+
+        let loose = Rules { min_step: 2, max_step: 5, allow_equal: true };
+        assert_eq!(check(&[1, 2, 7, 8, 9], &loose), Err(Violation { index: 0, values: (1, 2), rule: Rule::TooSmall { step: 1, min: 2 } }));
+        assert_eq!(check(&[4, 4, 2, 2, 0], &loose), Ok(()));
+        assert_eq!(check(&[4, 4, 2, 2, 4], &loose).unwrap_err().to_string(), "levels 3 and 4 (2 -> 4) break the decreasing direction");
+    }
+
+    #[test]
+    fn test_loose_rules() {
+        let rules = Rules { min_step: 1, max_step: 5, allow_equal: true };
+        let reports = read_reports("test");
+        assert_eq!(part_1(&reports, &rules), 5);
+        assert_eq!(part_2(&reports, 1, &rules), 6);
+    }
+
+    fn rules() -> impl Strategy<Value = Rules> {
+        (1..4u32, 0..3u32, any::<bool>()).prop_map(|(min_step, extra, allow_equal)| {
+            Rules { min_step, max_step: min_step + extra, allow_equal }
+        })
+    }
+
+    /// Reports built from small steps, so most of them are safe or nearly safe.
+    fn reports(max_len: usize) -> impl Strategy<Value = Vec<i32>> {
+        (0..20i32, prop::collection::vec(-4..=4i32, 0..max_len)).prop_map(|(start, steps)| {
+            std::iter::once(start).chain(steps.into_iter().scan(start, |level, step| {
+                *level += step;
+                Some(*level)
+            })).collect()
+        })
+    }
+
+    type Case = (Vec<i32>, Rules);
+    type CaseWithTolerance = (Vec<i32>, usize, Rules);
+
+    /// Every way of applying the Problem Dampener to one report.
+    fn dampener_variants() -> Variants<Case, bool> {
+        Variants::new()
+            .add("linear", |(report, rules): &Case| is_safe_dampened(report, rules))
+            .add("dp", |(report, rules): &Case| [Mode::Increasing, Mode::Decreasing].into_iter().any(|mode| min_removals(report, mode, 1, rules).is_some()))
+            .add("brute force", |(report, rules): &Case| safe_with_removals_brute(report, 1, rules))
+    }
+
+    /// Every way of checking a report with a tolerance of `k` removals.
+    fn removal_variants() -> Variants<CaseWithTolerance, bool> {
+        Variants::new()
+            .add("solver", |(report, k, rules): &CaseWithTolerance| safe_with_removals(report, *k, rules))
+            .add("brute force", |(report, k, rules): &CaseWithTolerance| safe_with_removals_brute(report, *k, rules))
+    }
 
-    println!("Safe count part 1 = {safe_count_pt1}");
-    println!("Safe count part 2 = {safe_count_pt2}");
+    #[test]
+    fn test_dampener_variants() {
+        dampener_variants().assert_agree((reports(10), rules()));
+    }
+
+    #[test]
+    fn test_removal_variants() {
+        removal_variants().assert_agree((reports(8), 0..4usize, rules()));
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| {
+            let reports = parse_reports(aoc::input::open(Some(path)));
+            let rules = Rules::default();
+            match part {
+                1 => part_1(&reports, &rules),
+                _ => part_2(&reports, 1, &rules),
+            }.to_string()
+        });
+    }
 }
@@ -1,49 +1,49 @@
 use clap::Parser;
+use tracing::instrument;
 use regex::Regex;
 use itertools::Itertools;
 use std::collections::BTreeMap;
-use std::fs;
+use std::{fs, path::Path};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Filename to read the input from.
-    filename: Option<String>,
+    #[command(flatten)]
+    options: aoc::Options,
 }
 
 fn main() {
 
     let cli = Args::parse();
+    aoc::init(&cli.options);
 
-    let filename = cli.filename.unwrap_or("files/test_input.txt".to_string());
+    let filename = aoc::input!(cli.options.input_name()).unwrap_or_else(|e| aoc::fail(e));
+    let report = aoc::report!(&cli.options, Some(&filename));
 
-    part_1(&filename);
+    report.part(1, "Solution", || part_1(&filename));
 
-    part_2(&filename);
+    report.part(2, "Solution", || part_2(&filename));
 
 }
 
-fn part_1 (filename: &str) -> i32 {
+#[instrument]
+fn part_1 (filename: &Path) -> i32 {
 
     let contents = read_from_file(filename);
 
-    let value = calculate_value_part_1(&contents);
-
-    println!("Part 1 Solution: {value}");
-    value
+    calculate_value_part_1(&contents)
 }
 
-fn part_2 (filename: &str) -> i32 {
+#[instrument]
+fn part_2 (filename: &Path) -> i32 {
 
     let contents = read_from_file(filename);
 
-    let value = calculate_value_part_2(&contents);
-
-    println!("Part 2 Solution: {value}");
-    value
+    calculate_value_part_2(&contents)
 }
 
-fn read_from_file(filename: &str) -> String {
+#[instrument]
+fn read_from_file(filename: &Path) -> String {
     fs::read_to_string(filename).expect("Unable to read file.")
 }
 
@@ -99,26 +99,35 @@ mod tests {
 
     #[test]
     fn test_part_1_test_input() {
-        let answer = part_1("files/test_input.txt");
+        let answer = part_1(&aoc::input!("test").unwrap());
         assert!(answer == 161);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_1_real_input() {
-        let answer = part_1("files/day3_input.txt");
+        let answer = part_1(&aoc::input!("real").unwrap());
         assert!(answer == 181345830);
     }
 
     #[test]
     fn test_part_2_test_input() {
-        let answer = part_2("files/test2_input.txt");
+        let answer = part_2(&aoc::input!("test2").unwrap());
         assert!(answer == 48);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_2_real_input() {
-        let _answer = part_2("files/day3_input.txt");
+        let _answer = part_2(&aoc::input!("real").unwrap());
         // assert!(answer == 181345830);
     }
-}
 
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| match part {
+            1 => part_1(path),
+            _ => part_2(path),
+        }.to_string());
+    }
+}
@@ -1,23 +1,26 @@
 use clap::Parser;
-use std::fs;
+use tracing::instrument;
+use std::{fs, path::Path};
 use strum::{EnumIter, IntoEnumIterator};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Filename to read the input from.
-    filename: Option<String>,
+    #[command(flatten)]
+    options: aoc::Options,
 }
 
 fn main() {
 
     let cli = Args::parse();
+    aoc::init(&cli.options);
 
-    let filename = cli.filename.unwrap_or("files/test_input.txt".to_string());
+    let filename = aoc::input!(cli.options.input_name()).unwrap_or_else(|e| aoc::fail(e));
+    let report = aoc::report!(&cli.options, Some(&filename));
 
-    part_1(&filename);
+    report.part(1, "Solution", || part_1(&filename));
 
-    part_2(&filename);
+    report.part(2, "Solution", || part_2(&filename));
 }
 
 #[derive(Debug, EnumIter)]
@@ -33,6 +36,7 @@ enum Direction {
 }
 
 #[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
 enum ToMatch {
     XMAS,
     MAS,
@@ -63,6 +67,11 @@ impl Location {
 struct Puzzle (Vec<Vec<char>>);
 
 impl Puzzle {
+    #[instrument(name = "parse", skip_all)]
+    fn from_string(content: &str) -> Puzzle {
+        Puzzle(content.lines().map(|l| l.chars().collect()).collect())
+    }
+
     /// Determine if the location is in bounds.
     fn in_bounds(&self, location: &Location) -> bool {
         location.row >= 0 && location.row < self.num_rows().try_into().unwrap() && location.col >= 0 && location.col < self.num_cols().try_into().unwrap()
@@ -80,31 +89,28 @@ impl Puzzle {
     fn num_cols (&self) -> usize { self.0.first().unwrap().len() }
 }
 
-fn part_1 (filename: &str) -> i32 {
+#[instrument]
+fn part_1 (filename: &Path) -> i32 {
 
     let contents = read_from_file(filename);
 
-    let puzzle: Puzzle = Puzzle(contents.lines().map(|l| l.chars().collect()).collect());
+    let puzzle = Puzzle::from_string(&contents);
 
-    let value = calculate_value_part_1(&puzzle);
-
-    println!("Part 1 Solution: {value}");
-    value
+    calculate_value_part_1(&puzzle)
 }
 
-fn part_2 (filename: &str) -> i32 {
+#[instrument]
+fn part_2 (filename: &Path) -> i32 {
 
     let contents = read_from_file(filename);
 
-    let puzzle: Puzzle = Puzzle(contents.lines().map(|l| l.chars().collect()).collect());
-
-    let value = calculate_value_part_2(&puzzle);
+    let puzzle = Puzzle::from_string(&contents);
 
-    println!("Part 2 Solution: {value}");
-    value
+    calculate_value_part_2(&puzzle)
 }
 
-fn read_from_file(filename: &str) -> String {
+#[instrument]
+fn read_from_file(filename: &Path) -> String {
     fs::read_to_string(filename).expect("Unable to read file.")
 }
 
@@ -190,26 +196,35 @@ mod tests {
 
     #[test]
     fn test_part_1_test_input() {
-        let answer = part_1("files/test_input.txt");
+        let answer = part_1(&aoc::input!("test").unwrap());
         assert!(answer == 18);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_1_real_input() {
-        let answer = part_1("files/day_4.txt");
+        let answer = part_1(&aoc::input!("real").unwrap());
         assert!(answer == 2397);
     }
 
     #[test]
     fn test_part_2_test_input() {
-        let answer = part_2("files/test_input.txt");
+        let answer = part_2(&aoc::input!("test").unwrap());
         assert!(answer == 9);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_2_real_input() {
-        let _answer = part_2("files/day_4.txt");
+        let _answer = part_2(&aoc::input!("real").unwrap());
         // assert!(answer == 1824);
     }
-}
 
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| match part {
+            1 => part_1(path),
+            _ => part_2(path),
+        }.to_string());
+    }
+}
@@ -0,0 +1,87 @@
+//! Generated print queues with a reference solver.
+//!
+//! Pages are ordered by a hidden random permutation and there is a rule for
+//! every pair of pages, so every update has exactly one correct order. The
+//! size is the number of updates, about half of which are already in order.
+
+use aoc::generate::{Generated, Rng};
+
+/// Number of distinct pages, as in the real inputs.
+const PAGES: usize = 49;
+
+pub fn generate(size: usize, seed: u64) -> Generated {
+    let mut rng = Rng::new(seed);
+
+    let mut order: Vec<u32> = (10..100).collect();
+    rng.shuffle(&mut order);
+    order.truncate(PAGES);
+
+    let mut rules: Vec<(u32, u32)> = (0..PAGES).flat_map(|i| (i + 1..PAGES).map(move |j| (i, j)))
+        .map(|(i, j)| (order[i], order[j]))
+        .collect();
+    rng.shuffle(&mut rules);
+
+    let updates: Vec<Vec<u32>> = (0..size).map(|_| {
+        let length = 2 * rng.range(2, 11) as usize + 1;
+        let mut update = order.clone();
+        rng.shuffle(&mut update);
+        update.truncate(length);
+        if rng.chance(0.5) {
+            update.sort_by_key(|page| order.iter().position(|p| p == page));
+        }
+        update
+    }).collect();
+
+    let mut input: String = rules.iter().map(|(a, b)| format!("{a}|{b}\n")).collect();
+    input.push('\n');
+    for update in &updates {
+        input += &format!("{}\n", update.iter().map(u32::to_string).collect::<Vec<_>>().join(","));
+    }
+
+    let answers = reference(&order, &updates).map(|answer| answer.to_string());
+    Generated { input, answers }
+}
+
+/// Sum the middle pages of the correct and of the fixed updates.
+///
+/// Works from each page's position in the hidden `order` rather than from the
+/// rules, so it shares nothing with the solver it checks.
+fn reference(order: &[u32], updates: &[Vec<u32>]) -> [u32; 2] {
+    let mut sums = [0, 0];
+
+    for update in updates {
+        let positions: Vec<usize> = update.iter()
+            .map(|page| order.iter().position(|p| p == page).expect("Every page is in the order."))
+            .collect();
+        let in_order = positions.windows(2).all(|w| w[0] < w[1]);
+        let mut sorted = positions;
+        sorted.sort();
+        sums[if in_order { 0 } else { 1 }] += order[sorted[sorted.len() / 2]];
+    }
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_inputs() {
+        aoc::generate::assert_solves(0..20, |_| 50, generate, |part, input| {
+            let (print_rules, mut print_jobs) = crate::parse(input);
+            match part {
+                1 => crate::calculate_value_part_1(&print_rules, &print_jobs),
+                _ => crate::calculate_value_part_2(&print_rules, &mut print_jobs),
+            }.to_string()
+        });
+    }
+
+    #[test]
+    fn test_reference() {
+        // The example has a rule for every pair of its pages, in this order.
+        let order = [97, 75, 47, 61, 53, 29, 13];
+        let (_, print_jobs) = crate::read_from_file(&aoc::input!("test").unwrap());
+        assert_eq!(reference(&order, &print_jobs.jobs), [143, 123]);
+        assert_eq!(generate(10, 3), generate(10, 3));
+    }
+}
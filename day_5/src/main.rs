@@ -1,46 +1,55 @@
 use clap::Parser;
-use std::{collections::HashMap, fs};
+use tracing::{event, instrument, Level};
+use std::{collections::HashMap, fs, path::Path};
 use regex::Regex;
 use petgraph::{graph::{DiGraph, NodeIndex}, Graph};
 use itertools::Itertools;
 
+mod generate;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Filename to read the input from.
-    filename: Option<String>,
+    #[command(flatten)]
+    options: aoc::Options,
+
+    #[command(flatten)]
+    generate: aoc::generate::GenerateOptions,
 }
 
 fn main() {
 
     let cli = Args::parse();
+    aoc::init(&cli.options);
+
+    if let Some(size) = cli.generate.size {
+        aoc::save_generated!(size, cli.generate.seed, &generate::generate(size, cli.generate.seed));
+        return;
+    }
 
-    let filename = cli.filename.unwrap_or("files/test_input.txt".to_string());
+    let filename = aoc::input!(cli.options.input_name()).unwrap_or_else(|e| aoc::fail(e));
+    let report = aoc::report!(&cli.options, Some(&filename));
 
-    part_1(&filename);
+    report.part(1, "Solution", || part_1(&filename));
 
-    part_2(&filename);
+    report.part(2, "Solution", || part_2(&filename));
 
 }
 
-fn part_1 (filename: &str) -> u32 {
+#[instrument]
+fn part_1 (filename: &Path) -> u32 {
 
     let (print_rules, print_jobs) = read_from_file(filename);
 
-    let value = calculate_value_part_1(&print_rules, &print_jobs);
-
-    println!("Part 1 Solution: {value}");
-    value
+    calculate_value_part_1(&print_rules, &print_jobs)
 }
 
-fn part_2 (filename: &str) -> u32 {
+#[instrument]
+fn part_2 (filename: &Path) -> u32 {
 
     let (print_rules, mut print_jobs) = read_from_file(filename);
 
-    let value = calculate_value_part_2(&print_rules, &mut print_jobs);
-
-    println!("Part 2 Solution: {value}");
-    value
+    calculate_value_part_2(&print_rules, &mut print_jobs)
 }
 
 #[derive(Debug)]
@@ -64,6 +73,12 @@ impl PrintRules {
         }                                         
     }
 
+    fn add_rule(&mut self, before: u32, after: u32) {
+        let before = self.get_or_add_node(before);
+        let after = self.get_or_add_node(after);
+        self.rules.add_edge(before, after, ());
+    }
+
     fn valid(&self, a: u32, b: u32) -> bool {
         let Some(a_n) = self.nodes.get(&a) else {return true};
         let Some(b_n) = self.nodes.get(&b) else {return true};
@@ -83,9 +98,12 @@ impl PrintJobs {
 }
 
 
-fn read_from_file(filename: &str) -> (PrintRules, PrintJobs) {
-    let contents = fs::read_to_string(filename).expect("Unable to read file.");
+#[instrument]
+fn read_from_file(filename: &Path) -> (PrintRules, PrintJobs) {
+    parse(&fs::read_to_string(filename).expect("Unable to read file."))
+}
 
+fn parse(contents: &str) -> (PrintRules, PrintJobs) {
     let re = Regex::new(r"^(\d+)\|(\d+)$").unwrap();
     let mut print_rules = PrintRules::new();
     let mut print_jobs = PrintJobs::new();
@@ -93,18 +111,14 @@ fn read_from_file(filename: &str) -> (PrintRules, PrintJobs) {
     for line in contents.lines() {
         let trimmed = line.trim();
         if let Some(cap) = re.captures(trimmed) {
-            let x: u32 = cap[1].parse().unwrap();
-            let y: u32 = cap[2].parse().unwrap();
-            let x = print_rules.get_or_add_node(x);
-            let y = print_rules.get_or_add_node(y);
-            print_rules.rules.add_edge(x, y, ());
+            print_rules.add_rule(cap[1].parse().unwrap(), cap[2].parse().unwrap());
         }
         else if !trimmed.is_empty() {
             print_jobs.jobs.push(trimmed.split(',').map(|x| x.parse().unwrap()).collect());                        
         }
     }
 
-    // println!("{print_rules:#?}");
+    event!(Level::INFO, "Read {} rules and {} jobs", print_rules.rules.edge_count(), print_jobs.jobs.len());
 
     (print_rules, print_jobs)
 }
@@ -112,7 +126,7 @@ fn read_from_file(filename: &str) -> (PrintRules, PrintJobs) {
 fn calculate_value_part_1(print_rules: &PrintRules, print_jobs: &PrintJobs) -> u32 {
     let mut count = 0;
     for i in 0..print_jobs.jobs.len() {
-        let valid = print_jobs.jobs[i].clone().into_iter().combinations(2).map(|r| print_rules.valid(r[0], r[1])).all(|x| x);
+        let valid = print_jobs.jobs[i].clone().into_iter().combinations(2).all(|r| print_rules.valid(r[0], r[1]));
 
         if valid {
             let middle = print_jobs.jobs[i].len()/2;
@@ -127,19 +141,10 @@ fn calculate_value_part_2(print_rules: &PrintRules, print_jobs: &mut PrintJobs)
     let mut count = 0;
     for i in 0..print_jobs.jobs.len() {
 
-        let valid = print_jobs.jobs[i].clone().into_iter().combinations(2).map(|r| print_rules.valid(r[0], r[1])).all(|x| x);
+        let valid = print_jobs.jobs[i].clone().into_iter().combinations(2).all(|r| print_rules.valid(r[0], r[1]));
 
         if !valid {
-            for j in 0..print_jobs.jobs[i].len() {
-                for k in j+1..print_jobs.jobs[i].len() {
-                    let a = print_jobs.jobs[i][j];
-                    let b = print_jobs.jobs[i][k];
-                    if !print_rules.valid(a, b) {
-                        print_jobs.jobs[i].swap(j, k);
-                    }
-
-                }
-            }
+            reorder(print_rules, &mut print_jobs.jobs[i]);
             let middle = print_jobs.jobs[i].len()/2;
             count += print_jobs.jobs[i][middle];
         }
@@ -148,32 +153,105 @@ fn calculate_value_part_2(print_rules: &PrintRules, print_jobs: &mut PrintJobs)
     count
 }
 
+/// Put a job's pages in an order that breaks no rule, swapping any pair that is the wrong way round.
+fn reorder(print_rules: &PrintRules, job: &mut [u32]) {
+    for j in 0..job.len() {
+        for k in j+1..job.len() {
+            if !print_rules.valid(job[j], job[k]) {
+                job.swap(j, k);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc::differential::Variants;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part_1_test_input() {
-        let answer = part_1("files/test_input.txt");
+        let answer = part_1(&aoc::input!("test").unwrap());
         assert!(answer == 143);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_1_real_input() {
-        let answer = part_1("files/day_5.txt");
+        let answer = part_1(&aoc::input!("real").unwrap());
         assert!(answer == 4135);
     }
 
     #[test]
     fn test_part_2_test_input() {
-        let answer = part_2("files/test_input.txt");
+        let answer = part_2(&aoc::input!("test").unwrap());
         assert!(answer == 123);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_2_real_input() {
-        let answer = part_2("files/day_5.txt");
+        let answer = part_2(&aoc::input!("real").unwrap());
         assert!(answer == 5285);
     }
-}
 
+    /// A hidden order of pages, with a rule for every pair of them, and a job made of some of those pages.
+    type Case = (Vec<u32>, Vec<u32>);
+
+    fn rules_from_order(order: &[u32]) -> PrintRules {
+        let mut print_rules = PrintRules::new();
+        for (a, b) in order.iter().tuple_combinations() {
+            print_rules.add_rule(*a, *b);
+        }
+        print_rules
+    }
+
+    fn cases() -> impl Strategy<Value = Case> {
+        prop::sample::subsequence((10..100).collect::<Vec<u32>>(), 1..12)
+            .prop_shuffle()
+            .prop_flat_map(|order| {
+                let job = prop::sample::subsequence(order.clone(), 0..=order.len()).prop_shuffle();
+                (Just(order), job)
+            })
+    }
+
+    /// Every way of putting a job back in order.
+    fn reorder_variants() -> Variants<Case, Vec<u32>> {
+        Variants::new()
+            .add("swap", |(order, job): &Case| {
+                let mut job = job.clone();
+                reorder(&rules_from_order(order), &mut job);
+                job
+            })
+            .add("sort", |(order, job): &Case| {
+                let print_rules = rules_from_order(order);
+                let mut job = job.clone();
+                job.sort_by(|a, b| match (print_rules.valid(*a, *b), print_rules.valid(*b, *a)) {
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    _ => std::cmp::Ordering::Equal,
+                });
+                job
+            })
+            .add("count", |(order, job): &Case| {
+                let print_rules = rules_from_order(order);
+                job.iter().copied()
+                    .sorted_by_key(|page| job.iter().filter(|other| !print_rules.valid(*page, **other)).count())
+                    .collect()
+            })
+    }
+
+    #[test]
+    fn test_reorder_variants() {
+        reorder_variants().assert_agree(cases());
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| match part {
+            1 => part_1(path),
+            _ => part_2(path),
+        }.to_string());
+    }
+}
@@ -0,0 +1,3 @@
+fn main() {
+    aoc::build::detect_inputs();
+}
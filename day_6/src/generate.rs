@@ -0,0 +1,114 @@
+//! Generated lab maps with a reference solver.
+//!
+//! The size is the side of a square map with obstacles scattered at random
+//! and the guard somewhere empty, facing up. Maps the guard never leaves are
+//! thrown away, as part 1 has no answer for them.
+
+use aoc::generate::{Generated, Rng};
+
+/// Share of cells that are obstacles.
+const DENSITY: f64 = 0.1;
+
+pub fn generate(size: usize, seed: u64) -> Generated {
+    let mut rng = Rng::new(seed);
+
+    loop {
+        let mut map: Vec<Vec<bool>> = (0..size).map(|_| (0..size).map(|_| rng.chance(DENSITY)).collect()).collect();
+        let start = (rng.below(size), rng.below(size));
+        map[start.0][start.1] = false;
+
+        if walk(&map, start).is_none() {
+            continue;
+        }
+
+        let mut input = String::new();
+        for (row, cells) in map.iter().enumerate() {
+            for (col, obstacle) in cells.iter().enumerate() {
+                input.push(match (*obstacle, (row, col) == start) {
+                    (true, _) => '#',
+                    (false, true) => '^',
+                    (false, false) => '.',
+                });
+            }
+            input.push('\n');
+        }
+
+        let (map, start) = parse(&input);
+        let answers = [walk(&map, start).unwrap(), loops(&map, start)].map(|answer| answer.to_string());
+        return Generated { input, answers };
+    }
+}
+
+/// Where the obstacles are and where the guard starts, read with the day's parser.
+fn parse(contents: &str) -> (Vec<Vec<bool>>, (usize, usize)) {
+    let (map, guard) = crate::Map::from_string(contents);
+    let obstacles = map.map.iter().map(|row| row.iter().map(|cell| *cell == crate::CellType::Obstacle).collect()).collect();
+    (obstacles, (guard.location.row as usize, guard.location.col as usize))
+}
+
+/// Number of cells the guard walks through before leaving, or `None` if they never do.
+fn walk(map: &[Vec<bool>], start: (usize, usize)) -> Option<usize> {
+    let (rows, cols) = (map.len() as i64, map[0].len() as i64);
+    // Up, right, down, left: turning right is the next direction.
+    let steps = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+    let mut seen = vec![[false; 4]; map.len() * map[0].len()];
+    let (mut row, mut col, mut direction) = (start.0 as i64, start.1 as i64, 0);
+
+    loop {
+        let cell = &mut seen[(row * cols + col) as usize];
+        if cell[direction] {
+            return None;
+        }
+        cell[direction] = true;
+
+        let (next_row, next_col) = (row + steps[direction].0, col + steps[direction].1);
+        if next_row < 0 || next_col < 0 || next_row >= rows || next_col >= cols {
+            return Some(seen.iter().filter(|cell| cell.iter().any(|d| *d)).count());
+        }
+        if map[next_row as usize][next_col as usize] {
+            direction = (direction + 1) % 4;
+        }
+        else {
+            (row, col) = (next_row, next_col);
+        }
+    }
+}
+
+/// Number of cells where one more obstacle traps the guard, trying every empty cell but the start.
+fn loops(map: &[Vec<bool>], start: (usize, usize)) -> usize {
+    let mut map = map.to_vec();
+    let mut count = 0;
+    for row in 0..map.len() {
+        for col in 0..map[row].len() {
+            if map[row][col] || (row, col) == start {
+                continue;
+            }
+            map[row][col] = true;
+            if walk(&map, start).is_none() {
+                count += 1;
+            }
+            map[row][col] = false;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_inputs() {
+        aoc::generate::assert_solves(0..200, |seed| 3 + seed as usize % 10, generate, |part, input| match part {
+            1 => crate::calculate_value_part_1(input),
+            _ => crate::calculate_value_part_2(input),
+        }.to_string());
+    }
+
+    #[test]
+    fn test_reference() {
+        let (map, start) = parse(&crate::read_from_file(&aoc::input!("test").unwrap()));
+        assert_eq!(walk(&map, start), Some(41));
+        assert_eq!(loops(&map, start), 6);
+    }
+}
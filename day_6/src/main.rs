@@ -1,47 +1,57 @@
 use clap::Parser;
-use std::{char, collections::HashSet, fs};
+use tracing::instrument;
+use std::{char, collections::HashSet, fs, path::Path};
 use rayon::prelude::*;
 
+mod generate;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Filename to read the input from.
-    filename: Option<String>,
+    #[command(flatten)]
+    options: aoc::Options,
+
+    #[command(flatten)]
+    generate: aoc::generate::GenerateOptions,
 }
 
 fn main() {
 
     let cli = Args::parse();
+    aoc::init(&cli.options);
 
-    let filename = cli.filename.unwrap_or("files/test_input.txt".to_string());
+    if let Some(size) = cli.generate.size {
+        aoc::save_generated!(size, cli.generate.seed, &generate::generate(size, cli.generate.seed));
+        return;
+    }
 
-    part_1(&filename);
+    let filename = aoc::input!(cli.options.input_name()).unwrap_or_else(|e| aoc::fail(e));
+    let report = aoc::report!(&cli.options, Some(&filename));
 
-    part_2(&filename);
+    report.part(1, "Solution", || part_1(&filename));
+
+    report.part(2, "Solution", || part_2(&filename));
 
 }
 
-fn part_1 (filename: &str) -> i32 {
+#[instrument]
+fn part_1 (filename: &Path) -> i32 {
 
     let contents = read_from_file(filename);
 
-    let value = calculate_value_part_1(&contents);
-
-    println!("Part 1 Solution: {value}");
-    value
+    calculate_value_part_1(&contents)
 }
 
-fn part_2 (filename: &str) -> i32 {
+#[instrument]
+fn part_2 (filename: &Path) -> i32 {
 
     let contents = read_from_file(filename);
 
-    let value = calculate_value_part_2(&contents);
-
-    println!("Part 2 Solution: {value}");
-    value
+    calculate_value_part_2(&contents)
 }
 
-fn read_from_file(filename: &str) -> String {
+#[instrument]
+fn read_from_file(filename: &Path) -> String {
     fs::read_to_string(filename).expect("Unable to read file.")
 }
 
@@ -102,6 +112,7 @@ impl Map {
         self.map[row][col] = CellType::Obstacle;
     }
 
+    #[instrument(name = "parse", skip_all)]
     fn from_string(content: &str) -> (Self, Guard)  {
         let mut guard  = Guard::new(Direction::North, 0, 0);
 
@@ -207,7 +218,7 @@ impl Location {
 
 fn calculate_value_part_1(contents: &str) -> i32 {
     let (mut map, mut guard) = Map::from_string(contents);
-
+    map.visit_location(&guard.location);
 
     loop {
         let result = guard.move_guard(&mut map);
@@ -229,12 +240,10 @@ fn calculate_value_part_1(contents: &str) -> i32 {
 fn calculate_value_part_2(contents: &str) -> i32 {
     let (map, guard) = Map::from_string(contents);
 
-    let guard_next_loc = guard.location.next_location(&Direction::North);
-
     (0..map.map.len()).into_par_iter().map(
         |i| {
             (0..map.map[i].len()).into_par_iter().map(|j| {
-                    if map.map[i][j] == CellType::Unvisited && !(i == guard_next_loc.row as usize && j == guard_next_loc.col as usize ) {
+                    if map.map[i][j] == CellType::Unvisited && !(i == guard.location.row as usize && j == guard.location.col as usize) {
                         let mut map_ut = map.clone();
                         let mut guard_ut = guard.clone();
                         map_ut.add_obstacle(&Location { row: i as i32, col: j as i32 });
@@ -266,26 +275,47 @@ mod tests {
 
     #[test]
     fn test_part_1_test_input() {
-        let answer = part_1("files/test_input.txt");
+        let answer = part_1(&aoc::input!("test").unwrap());
         assert!(answer == 41);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_1_real_input() {
-        let answer = part_1("files/day_6.txt");
+        let answer = part_1(&aoc::input!("real").unwrap());
         assert!(answer == 4964);
     }
 
     #[test]
     fn test_part_2_test_input() {
-        let answer = part_2("files/test_input.txt");
+        let answer = part_2(&aoc::input!("test").unwrap());
         assert!(answer == 6);
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_2_real_input() {
-        let answer = part_2("files/day_6.txt");
+        let answer = part_2(&aoc::input!("real").unwrap());
         assert!(answer == 1740);
     }
-}
 
+    #[test]
+    fn test_start_cell_is_visited() {
+        assert_eq!(calculate_value_part_1("...\n#^#\n.#.\n"), 2);
+        assert_eq!(calculate_value_part_1("^\n"), 1);
+    }
+
+    #[test]
+    fn test_obstacle_in_front_of_guard() {
+        // Blocking the only way out traps the guard, the start itself is never an option.
+        assert_eq!(calculate_value_part_2("...\n#^#\n.#.\n"), 1);
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| match part {
+            1 => part_1(path),
+            _ => part_2(path),
+        }.to_string());
+    }
+}
@@ -0,0 +1,89 @@
+//! Generated calibration equations with a reference solver.
+//!
+//! The size is the number of equations. Half of them are built from random
+//! operators so they can be solved, the rest are nudged off by a little and
+//! most likely cannot. Numbers include zero and are kept small enough that
+//! every test value fits in a u64.
+
+use crate::Operators;
+use aoc::generate::{Generated, Rng};
+
+pub fn generate(size: usize, seed: u64) -> Generated {
+    let mut rng = Rng::new(seed);
+
+    let equations: Vec<(u64, Vec<u64>)> = (0..size).map(|_| {
+        let numbers: Vec<u64> = (0..rng.range(2, 8)).map(|_| rng.range(0, 99)).collect();
+        let ops: Vec<u64> = (1..numbers.len()).map(|_| rng.range(0, 2)).collect();
+        let value = evaluate(&numbers, &ops) as u64;
+        let test_value = match rng.chance(0.5) {
+            true => value,
+            false => value.saturating_sub(rng.range(1, 10)).max(1),
+        };
+        (test_value, numbers)
+    }).collect();
+
+    let input: String = equations.iter()
+        .map(|(test_value, numbers)| format!("{test_value}: {}\n", numbers.iter().map(u64::to_string).collect::<Vec<_>>().join(" ")))
+        .collect();
+
+    let equations = Operators::new(&input);
+    let answers = [2, 3].map(|operators| reference(&equations, operators).to_string());
+    Generated { input, answers }
+}
+
+/// Apply operators left to right, where 0 is `+`, 1 is `*` and 2 is `||`.
+pub fn evaluate(numbers: &[u64], ops: &[u64]) -> u128 {
+    numbers[1..].iter().zip(ops).fold(numbers[0] as u128, |value, (number, op)| {
+        let number = *number as u128;
+        match op {
+            0 => value + number,
+            1 => value * number,
+            _ => value * 10u128.pow(number.to_string().len() as u32) + number,
+        }
+    })
+}
+
+/// Sum the test values of the equations that are [`solvable`] with the first `operators` operators.
+fn reference(equations: &Operators<u64>, operators: u64) -> u64 {
+    equations.iter()
+        .filter(|equation| solvable(equation.test_value, &equation.numbers, operators))
+        .map(|equation| equation.test_value)
+        .sum()
+}
+
+/// Whether some choice of the first `operators` operators reaches the test value,
+/// found by counting through every choice in base `operators`.
+pub fn solvable(test_value: u64, numbers: &[u64], operators: u64) -> bool {
+    let choices = operators.pow(numbers.len() as u32 - 1);
+    (0..choices).any(|choice| {
+        let ops: Vec<u64> = (1..numbers.len()).scan(choice, |rest, _| {
+            let op = *rest % operators;
+            *rest /= operators;
+            Some(op)
+        }).collect();
+        evaluate(numbers, &ops) == test_value as u128
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+
+    #[test]
+    fn test_generated_inputs() {
+        for solver in [Solver::Forward, Solver::Reverse] {
+            aoc::generate::assert_solves(0..20, |_| 50, generate, |part, input| match part {
+                1 => crate::calculate_value_part_1::<u64>(input, solver),
+                _ => crate::calculate_value_part_2::<u64>(input, solver),
+            }.to_string());
+        }
+    }
+
+    #[test]
+    fn test_reference() {
+        let equations = Operators::new(&crate::read_from_file(&aoc::input!("test").unwrap()));
+        assert_eq!(reference(&equations, 2), 3749);
+        assert_eq!(reference(&equations, 3), 11387);
+    }
+}
@@ -1,189 +1,689 @@
-use clap::Parser;
-use std::fs;
-use strum::{EnumIter, IntoEnumIterator};
+use clap::{Parser, ValueEnum};
+use tracing::instrument;
+use num_bigint::BigUint;
+use aoc::report::Report;
+use std::{fs, path::Path, time::Instant};
+use number::Number;
+use operations::{Add, Operation, OperatorSet, Precedence};
+
+mod generate;
+mod number;
+mod operations;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Filename to read the input from.
-    filename: Option<String>,
+    /// Print the operator sequence for each solvable equation.
+    #[arg(short, long)]
+    show: bool,
+
+    /// Print every operator sequence instead of only the first one found.
+    #[arg(short, long, requires = "show")]
+    all: bool,
+
+    /// Also solve with a custom comma separated operator set.
+    ///
+    /// Known operators: `+`, `*`, `||` (concatenation), `-`, `^` (xor) and `**` (power).
+    #[arg(long, value_name = "OPS")]
+    ops: Option<OperatorSet>,
+
+    /// Search strategy used to check each equation.
+    #[arg(long, value_enum, default_value_t = Solver::Forward)]
+    solver: Solver,
+
+    /// Time both solvers on both parts and check that they agree.
+    #[arg(long)]
+    bench: bool,
+
+    /// Use arbitrary precision numbers for test values that do not fit in a u64.
+    #[arg(long)]
+    big: bool,
+
+    /// Also evaluate with operator precedence and compare against left to right.
+    ///
+    /// Levels from loosest to tightest: `^` 0, `+` `-` 1, `*` 2, `**` 3, `||` 4.
+    #[arg(long)]
+    precedence: bool,
+
+    /// Precedence level of `||` when evaluating with precedence.
+    #[arg(long, value_name = "LEVEL", requires = "precedence")]
+    concat_precedence: Option<u8>,
+
+    #[command(flatten)]
+    options: aoc::Options,
+
+    #[command(flatten)]
+    generate: aoc::generate::GenerateOptions,
+}
+
+/// How an equation is evaluated.
+#[derive(Debug, Clone, Copy)]
+enum Evaluation {
+    /// Strictly left to right, as in the puzzle.
+    LeftToRight,
+    /// Tighter binding operators first, then left to right.
+    Precedence(Precedence),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Solver {
+    /// Try every operator left to right, starting from the first number.
+    Forward,
+    /// Undo operators right to left, starting from the test value.
+    Reverse,
+}
+
+impl Solver {
+    /// The solver to use with `ops`, as the reverse solver needs every operator to be invertible.
+    fn for_ops(self, ops: &OperatorSet) -> Solver {
+        match self {
+            Solver::Reverse if !ops.invertible() => Solver::Forward,
+            solver => solver,
+        }
+    }
 }
 
 fn main() {
 
     let cli = Args::parse();
+    aoc::init(&cli.options);
+
+    if let Some(size) = cli.generate.size {
+        aoc::save_generated!(size, cli.generate.seed, &generate::generate(size, cli.generate.seed));
+        return;
+    }
+
+    let filename = aoc::input!(cli.options.input_name()).unwrap_or_else(|e| aoc::fail(e));
+    let report = aoc::report!(&cli.options, Some(&filename));
+
+    if cli.bench {
+        bench(&filename, &report);
+        return;
+    }
+
+    if cli.big {
+        run::<BigUint>(&filename, &cli, &report);
+    }
+    else {
+        run::<u64>(&filename, &cli, &report);
+    }
+
+}
+
+/// Solve both parts, and the custom operator set if given, using numbers of type `N`.
+fn run<N: Number>(filename: &Path, cli: &Args, report: &Report) {
+
+    let evaluation = match cli.precedence {
+        true => Evaluation::Precedence(Precedence {
+            concat: cli.concat_precedence.unwrap_or(Precedence::default().concat),
+        }),
+        false => Evaluation::LeftToRight,
+    };
+
+    report.part(1, "Solution", || part_1::<N>(filename, cli.solver));
 
-    let filename = cli.filename.unwrap_or("files/test_input.txt".to_string());
+    if cli.precedence {
+        compare::<N>(filename, "Part 1", &OperatorSet::part_1(), evaluation, report);
+    }
+
+    if cli.show {
+        show_solutions::<N>(filename, &OperatorSet::part_1(), evaluation, cli.all, report);
+    }
 
-    part_1(&filename);
+    report.part(2, "Solution", || part_2::<N>(filename, cli.solver));
 
-    part_2(&filename);
+    if cli.precedence {
+        compare::<N>(filename, "Part 2", &OperatorSet::part_2(), evaluation, report);
+    }
+
+    if cli.show {
+        show_solutions::<N>(filename, &OperatorSet::part_2(), evaluation, cli.all, report);
+    }
 
+    if let Some(ops) = &cli.ops {
+        custom::<N>(filename, ops, cli.solver, report);
+
+        if cli.precedence {
+            compare::<N>(filename, &format!("With {ops}"), ops, evaluation, report);
+        }
+
+        if cli.show {
+            show_solutions::<N>(filename, ops, evaluation, cli.all, report);
+        }
+    }
 }
 
-fn part_1 (filename: &str) -> u64 {
+#[instrument]
+fn part_1<N: Number> (filename: &Path, solver: Solver) -> N {
 
     let contents = read_from_file(filename);
 
-    let value = calculate_value_part_1(&contents);
+    calculate_value_part_1::<N>(&contents, solver)
+}
 
-    println!("Part 1 Solution: {value}");
-    value
+#[instrument]
+fn part_2<N: Number> (filename: &Path, solver: Solver) -> N {
+
+    let contents = read_from_file(filename);
+
+    calculate_value_part_2::<N>(&contents, solver)
 }
 
-fn part_2 (filename: &str) -> u64 {
+fn custom<N: Number> (filename: &Path, ops: &OperatorSet, solver: Solver, report: &Report) -> N {
 
     let contents = read_from_file(filename);
 
-    let value = calculate_value_part_2(&contents);
+    if solver.for_ops(ops) != solver {
+        aoc::extra!(report, "Operators {ops} are not all invertible, using the forward solver.");
+    }
+
+    let value = calculate_value::<N>(&contents, ops, solver);
 
-    println!("Part 2 Solution: {value}");
+    aoc::extra!(report, "Solution with {ops}: {value}");
     value
 }
 
-fn read_from_file(filename: &str) -> String {
+/// Run both parts with every solver, print the timings and check the answers match.
+fn bench(filename: &Path, report: &Report) {
+    let contents = read_from_file(filename);
+
+    for (part, ops) in [(1, OperatorSet::part_1()), (2, OperatorSet::part_2())] {
+        let mut answers = Vec::new();
+        for solver in Solver::value_variants() {
+            let start = Instant::now();
+            let value = calculate_value::<u64>(&contents, &ops, *solver);
+            let elapsed = start.elapsed();
+            aoc::extra!(report, "Part {part} {solver:?}: {value} in {elapsed:?}");
+            answers.push(value);
+        }
+        assert!(answers.iter().all(|a| *a == answers[0]), "Solvers disagree on part {part}: {answers:?}");
+    }
+}
+
+#[instrument]
+fn read_from_file(filename: &Path) -> String {
     fs::read_to_string(filename).expect("Unable to read file.")
 }
 
-struct Operators {
-    operators: Vec<Operator>
+/// Print the answer and solution counts under both left to right and precedence evaluation.
+fn compare<N: Number>(filename: &Path, label: &str, ops: &OperatorSet, precedence: Evaluation, report: &Report) {
+    let contents = read_from_file(filename);
+
+    for (name, evaluation) in [("Left to right", Evaluation::LeftToRight), ("Precedence", precedence)] {
+        let (value, equations, assignments) = count_solutions::<N>(&contents, ops, evaluation);
+        aoc::extra!(report, "{label} {name}: {value} from {equations} equations with {assignments} operator assignments");
+    }
 }
 
-impl Operators {
-    fn new(content: &str) -> Operators {
+/// Print the expression that reaches each solvable test value.
+fn show_solutions<N: Number>(filename: &Path, ops: &OperatorSet, evaluation: Evaluation, all: bool, report: &Report) {
+    let contents = read_from_file(filename);
+    let operators = Operators::<N>::new(&contents);
+
+    for operator in operators.iter() {
+        for ops in operator.solutions(ops, evaluation, all) {
+            aoc::extra!(report, "{}: {}", operator.test_value, operator.expression(&ops));
+        }
+    }
+}
+
+struct Operators<N> {
+    operators: Vec<Operator<N>>
+}
+
+impl<N: Number> Operators<N> {
+    #[instrument(name = "parse", skip_all)]
+    fn new(content: &str) -> Operators<N> {
         let operators = content.lines().map(|line| {
             let mut split = line.split(':');
-            let test_input = split.next().unwrap().parse().unwrap();
-            let values = split.next().unwrap().split_whitespace().map(|w| w.parse::<u64>().unwrap()).collect();
+            let test_input = Operators::parse_number(split.next().unwrap());
+            let values = split.next().unwrap().split_whitespace().map(Operators::parse_number).collect();
             Operator{test_value: test_input, numbers: values}
         }).collect();
 
         Operators{operators}
     }
-    fn iter(&self) -> impl Iterator<Item = &Operator> {
+
+    fn parse_number(word: &str) -> N {
+        word.trim().parse().unwrap_or_else(|_| panic!("Unable to parse number '{word}', use --big for values that do not fit in a u64."))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Operator<N>> {
         self.operators.iter()
     }
 }
 
-struct Operator {
-    test_value: u64,
-    numbers: Vec<u64>,
+struct Operator<N> {
+    test_value: N,
+    numbers: Vec<N>,
 }
 
-impl Operator {
-    fn valid_test1(&self) -> bool {
-        Operator::is_valid1(self.test_value, &self.numbers[1..], self.numbers[0])
+/// An operator sequence, one entry per gap between the numbers.
+type Solution = Vec<&'static dyn Operation>;
+
+impl<N: Number> Operator<N> {
+    fn is_valid(&self, ops: &OperatorSet, solver: Solver) -> bool {
+        match solver {
+            Solver::Forward => Operator::is_valid_rec(&self.test_value, &self.numbers[1..], &self.numbers[0], ops),
+            Solver::Reverse => Operator::is_valid_reverse(&self.test_value, &self.numbers, ops),
+        }
+    }
+
+    /// Find the operator sequences that produce the test value.
+    ///
+    /// Stops at the first sequence unless `all` is set. An empty result means
+    /// the equation cannot be solved.
+    fn solutions(&self, ops: &OperatorSet, evaluation: Evaluation, all: bool) -> Vec<Solution> {
+        let mut solutions = Vec::new();
+        let mut current = Vec::new();
+        match evaluation {
+            Evaluation::LeftToRight => Operator::find_solutions(&self.test_value, &self.numbers[1..], &self.numbers[0], ops, all, &mut current, &mut solutions),
+            Evaluation::Precedence(precedence) => Operator::find_solutions_precedence(&self.test_value, &self.numbers[1..], &[], &self.numbers[0], ops, precedence, all, &mut current, &mut solutions),
+        }
+        solutions
     }
 
-    fn valid_test2(&self) -> bool {
-        Operator::is_valid2(self.test_value, &self.numbers[1..], self.numbers[0])
+    /// Format the numbers joined by the given operators, e.g. `81 + 40 * 27`.
+    fn expression(&self, ops: &[&dyn Operation]) -> String {
+        let mut expression = self.numbers[0].to_string();
+        for (op, number) in ops.iter().zip(&self.numbers[1..]) {
+            expression += &format!(" {} {number}", op.symbol());
+        }
+        expression
     }
 
-    fn is_valid1(test_value: u64, values: &[u64], partial: u64) -> bool {
+    /// Overflowing branches are pruned, as `Number::apply` returns `None` for them.
+    fn find_solutions(test_value: &N, values: &[N], partial: &N, ops: &OperatorSet, all: bool, current: &mut Solution, solutions: &mut Vec<Solution>) {
         if values.is_empty() {
-            return test_value == partial;
+            if test_value == partial {
+                solutions.push(current.clone());
+            }
+            return;
         }
 
-        for op in Operations::iter() {
-            let partial = match op {
-                Operations::Add => partial + values.first().unwrap(),
-                Operations::Multiply => partial * values.first().unwrap(),
-                Operations::Concat => continue,
-            };
+        for op in ops.iter() {
+            let Some(partial) = N::apply(op, partial, &values[0]) else { continue };
 
-            let result = Operator::is_valid1(test_value, &values[1..], partial);
+            current.push(op);
+            Operator::find_solutions(test_value, &values[1..], &partial, ops, all, current, solutions);
+            current.pop();
 
-            if result {
-                return true;
+            if !all && !solutions.is_empty() {
+                return;
             }
-            
         }
-        false
     }
-    fn is_valid2(test_value: u64, values: &[u64], partial: u64) -> bool {
+
+    /// Search operator sequences evaluated with precedence.
+    ///
+    /// `stack` holds left hand sides waiting on an operator that binds looser
+    /// than the one before `operand`. Choosing the next operator first reduces
+    /// every entry that binds at least as tightly.
+    #[allow(clippy::too_many_arguments)]
+    fn find_solutions_precedence(test_value: &N, values: &[N], stack: &[(N, &'static dyn Operation)], operand: &N, ops: &OperatorSet, precedence: Precedence, all: bool, current: &mut Solution, solutions: &mut Vec<Solution>) {
+        if values.is_empty() {
+            let mut stack = stack.to_vec();
+            if Operator::reduce(&mut stack, operand.clone(), 0, precedence).as_ref() == Some(test_value) {
+                solutions.push(current.clone());
+            }
+            return;
+        }
+
+        for op in ops.iter() {
+            let mut stack = stack.to_vec();
+            let Some(lhs) = Operator::reduce(&mut stack, operand.clone(), precedence.level(op), precedence) else { continue };
+            stack.push((lhs, op));
+
+            current.push(op);
+            Operator::find_solutions_precedence(test_value, &values[1..], &stack, &values[0], ops, precedence, all, current, solutions);
+            current.pop();
+
+            if !all && !solutions.is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// Apply the pending operators on the stack that bind at least as tightly as `level`.
+    fn reduce(stack: &mut Vec<(N, &'static dyn Operation)>, mut operand: N, level: u8, precedence: Precedence) -> Option<N> {
+        while let Some((lhs, op)) = stack.last() {
+            if precedence.level(*op) < level {
+                break;
+            }
+            operand = N::apply(*op, lhs, &operand)?;
+            stack.pop();
+        }
+        Some(operand)
+    }
+
+    fn is_valid_rec(test_value: &N, values: &[N], partial: &N, ops: &OperatorSet) -> bool {
         if values.is_empty() {
             return test_value == partial;
         }
 
-        for op in Operations::iter() {
-            let partial = match op {
-                Operations::Add => partial + values.first().unwrap(),
-                Operations::Multiply => partial * values.first().unwrap(),
-                Operations::Concat => (partial.to_string() + &values.first().unwrap().to_string()).parse().unwrap(),
-            };
+        for op in ops.iter() {
+            let Some(partial) = N::apply(op, partial, &values[0]) else { continue };
 
-            let result = Operator::is_valid2(test_value, &values[1..], partial);
+            if Operator::is_valid_rec(test_value, &values[1..], &partial, ops) {
+                return true;
+            }
+        }
+        false
+    }
 
-            if result {
+    /// Work backwards from the target, undoing the operator applied to the last number.
+    ///
+    /// Branches where an operator cannot be undone (e.g. the target is not
+    /// divisible by the last number) are pruned without exploring them. When
+    /// the operator gives the target whatever comes before it, like `* 0`, the
+    /// rest only has to evaluate without overflowing.
+    fn is_valid_reverse(target: &N, values: &[N], ops: &OperatorSet) -> bool {
+        let (last, rest) = values.split_last().unwrap();
+        if rest.is_empty() {
+            return target == last;
+        }
+
+        for op in ops.iter() {
+            if N::absorbs(op, target, last) && Operator::evaluates(&rest[0], &rest[1..], ops) {
+                return true;
+            }
+
+            let Some(target) = N::inverse(op, target, last) else { continue };
+
+            if Operator::is_valid_reverse(&target, rest, ops) {
                 return true;
             }
         }
         false
+    }
+
+    /// Whether some operators combine `partial` with `values` without overflowing.
+    fn evaluates(partial: &N, values: &[N], ops: &OperatorSet) -> bool {
+        let Some((next, rest)) = values.split_first() else { return true };
 
+        ops.iter().any(|op| N::apply(op, partial, next).is_some_and(|partial| Operator::evaluates(&partial, rest, ops)))
     }
 }
 
-#[derive(Debug, EnumIter)]
-enum Operations {
-    Add,
-    Multiply,
-    Concat,
-}
+/// Sum the test values of every equation that can be solved with `ops`.
+///
+/// The reverse solver needs every operator to be invertible and falls back to
+/// the forward solver otherwise, see [`Solver::for_ops`].
+fn calculate_value<N: Number>(contents: &str, ops: &OperatorSet, solver: Solver) -> N {
+    let operators = Operators::<N>::new(contents);
+    let solver = solver.for_ops(ops);
 
-fn calculate_value_part_1(contents: &str) -> u64 {
-    let operators = Operators::new(contents);
-    
-    let mut count: u64 = 0;
+    let mut count = N::zero();
     for operator in operators.iter() {
-        if operator.valid_test1() {
-            count += operator.test_value;
+        if operator.is_valid(ops, solver) {
+            count = N::apply(&Add, &count, &operator.test_value).expect("Sum of test values overflowed, use --big.");
         }
     }
     count
 }
 
-fn calculate_value_part_2(contents: &str) -> u64 {
-    let operators = Operators::new(contents);
-    
-    let mut count: u64 = 0;
+/// Sum, count the solvable equations and count all operator assignments that solve them.
+fn count_solutions<N: Number>(contents: &str, ops: &OperatorSet, evaluation: Evaluation) -> (N, usize, usize) {
+    let operators = Operators::<N>::new(contents);
+
+    let mut count = N::zero();
+    let mut equations = 0;
+    let mut assignments = 0;
     for operator in operators.iter() {
-        if operator.valid_test2() {
-            count += operator.test_value;
+        let solutions = operator.solutions(ops, evaluation, true);
+        if !solutions.is_empty() {
+            count = N::apply(&Add, &count, &operator.test_value).expect("Sum of test values overflowed, use --big.");
+            equations += 1;
+            assignments += solutions.len();
         }
     }
-    count
+    (count, equations, assignments)
+}
+
+fn calculate_value_part_1<N: Number>(contents: &str, solver: Solver) -> N {
+    calculate_value(contents, &OperatorSet::part_1(), solver)
+}
+
+fn calculate_value_part_2<N: Number>(contents: &str, solver: Solver) -> N {
+    calculate_value(contents, &OperatorSet::part_2(), solver)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc::differential::Variants;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part_1_test_input() {
-        let answer = part_1("files/test_input.txt");
+        let answer = part_1::<u64>(&aoc::input!("test").unwrap(), Solver::Forward);
         let correct = 3749;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_1_real_input() {
-        let answer = part_1("files/day_7.txt");
+        let answer = part_1::<u64>(&aoc::input!("real").unwrap(), Solver::Forward);
         let correct = 21572148763543;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
 
     #[test]
     fn test_part_2_test_input() {
-        let answer = part_2("files/test_input.txt");
+        let answer = part_2::<u64>(&aoc::input!("test").unwrap(), Solver::Forward);
         let correct = 11387;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_2_real_input() {
-        let answer = part_2("files/day_7.txt");
+        let answer = part_2::<u64>(&aoc::input!("real").unwrap(), Solver::Forward);
         let correct = 581941094529163;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
-}
 
+    #[test]
+    fn test_reverse_solver() {
+        let filename = &aoc::input!("test").unwrap();
+        assert_eq!(part_1::<u64>(filename, Solver::Reverse), 3749);
+        assert_eq!(part_2::<u64>(filename, Solver::Reverse), 11387);
+    }
+
+    #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
+    fn test_reverse_solver_real_input() {
+        let filename = &aoc::input!("real").unwrap();
+        let answer = part_1::<u64>(filename, Solver::Reverse);
+        let correct = 21572148763543;
+        assert!(answer == correct, "Answer is {answer} not {correct}.");
+        let answer = part_2::<u64>(filename, Solver::Reverse);
+        let correct = 581941094529163;
+        assert!(answer == correct, "Answer is {answer} not {correct}.");
+    }
+
+    #[test]
+    fn test_solutions_test_input() {
+        let operators = Operators::<u64>::new(&read_from_file(&aoc::input!("test").unwrap()));
+        let expressions: Vec<Vec<String>> = operators.iter()
+            .map(|o| o.solutions(&OperatorSet::part_2(), Evaluation::LeftToRight, true).iter().map(|ops| o.expression(ops)).collect())
+            .collect();
+
+        assert_eq!(expressions[0], vec!["10 * 19"]);
+        assert_eq!(expressions[1], vec!["81 + 40 * 27", "81 * 40 + 27"]);
+        assert!(expressions[2].is_empty());
+        assert_eq!(expressions[4], vec!["6 * 8 || 6 * 15"]);
+        assert_eq!(expressions[8], vec!["11 + 6 * 16 + 20"]);
+    }
+
+    #[test]
+    fn test_solutions_first_only() {
+        let operators = Operators::<u64>::new("3267: 81 40 27");
+        let operator = operators.iter().next().unwrap();
+        let solutions = operator.solutions(&OperatorSet::part_1(), Evaluation::LeftToRight, false);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(operator.expression(&solutions[0]), "81 + 40 * 27");
+    }
+
+    #[test]
+    fn test_precedence() {
+        let operators = Operators::<u64>::new("3267: 81 40 27\n292: 11 6 16 20\n127: 11 6 16 20\n1: 2 3 5");
+        let precedence = Evaluation::Precedence(Precedence::default());
+        let expressions: Vec<Vec<String>> = operators.iter()
+            .map(|o| o.solutions(&"+,*,-".parse().unwrap(), precedence, true).iter().map(|ops| o.expression(ops)).collect())
+            .collect();
+
+        assert_eq!(expressions[0], vec!["81 * 40 + 27"]);
+        assert!(expressions[1].is_empty());
+        assert_eq!(expressions[2], vec!["11 + 6 * 16 + 20"]);
+        assert_eq!(expressions[3], vec!["2 * 3 - 5"]);
+    }
+
+    #[test]
+    fn test_precedence_test_input() {
+        let contents = read_from_file(&aoc::input!("test").unwrap());
+        let left_to_right = count_solutions::<u64>(&contents, &OperatorSet::part_2(), Evaluation::LeftToRight);
+        assert_eq!(left_to_right, (11387, 6, 7));
+
+        let cases = [
+            (OperatorSet::part_1(), Precedence::default(), (3457, 2, 2)),
+            (OperatorSet::part_2(), Precedence::default(), (3805, 4, 4)),
+            (OperatorSet::part_2(), Precedence { concat: 0 }, (3613, 3, 3)),
+        ];
+        for (ops, precedence, correct) in cases {
+            let answer = count_solutions::<u64>(&contents, &ops, Evaluation::Precedence(precedence));
+            assert_eq!(answer, correct, "Wrong counts for {ops} with {precedence:?}.");
+        }
+    }
+
+    #[test]
+    fn test_parse_operator_set() {
+        let ops: OperatorSet = "+,*,||,-,^,**".parse().unwrap();
+        assert_eq!(ops.to_string(), "+,*,||,-,^,**");
+        assert!("+,/".parse::<OperatorSet>().is_err());
+    }
+
+    fn calculate_value_both(contents: &str, ops: &OperatorSet) -> u64 {
+        let forward = calculate_value(contents, ops, Solver::Forward);
+        let reverse = calculate_value(contents, ops, Solver::Reverse);
+        assert_eq!(forward, reverse, "Solvers disagree for {ops}.");
+
+        let big = calculate_value::<BigUint>(contents, ops, Solver::Forward);
+        assert_eq!(big, BigUint::from(forward), "Big integers disagree for {ops}.");
+        forward
+    }
+
+    #[test]
+    fn test_custom_operators() {
+        let contents = "3: 5 2\n7: 5 2\n25: 5 2\n10: 5 2";
+        assert_eq!(calculate_value_both(contents, &"-".parse().unwrap()), 3);
+        assert_eq!(calculate_value_both(contents, &"^".parse().unwrap()), 7);
+        assert_eq!(calculate_value_both(contents, &"**".parse().unwrap()), 25);
+        assert_eq!(calculate_value_both(contents, &"-,^,**,*".parse().unwrap()), 45);
+
+        assert_eq!(Solver::Reverse.for_ops(&"+,**".parse().unwrap()), Solver::Forward);
+        assert_eq!(Solver::Reverse.for_ops(&OperatorSet::part_2()), Solver::Reverse);
+    }
+
+    #[test]
+    fn test_inverse() {
+        for op in operations::ALL_OPERATIONS.iter().filter(|op| op.has_inverse()) {
+            for (lhs, rhs) in [(15, 6), (81, 40), (3267, 27), (100, 10)] {
+                if let Some(result) = op.apply(lhs, rhs) {
+                    assert_eq!(op.inverse(result, rhs), Some(lhs), "{lhs} {} {rhs}", op.symbol());
+
+                    let (lhs, rhs, result) = (BigUint::from(lhs), BigUint::from(rhs), BigUint::from(result));
+                    assert_eq!(op.apply_big(&lhs, &rhs), Some(result.clone()), "{lhs} {} {rhs}", op.symbol());
+                    assert_eq!(op.inverse_big(&result, &rhs), Some(lhs.clone()), "{lhs} {} {rhs}", op.symbol());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow_is_pruned() {
+        let max = u64::MAX;
+        let contents = format!("{max}: {max} 1 1\n4: 2 2");
+
+        // `max + 1` overflows, only `max * 1 * 1` and `2 * 2`/`2 + 2` are valid.
+        let operators = Operators::<u64>::new(&contents);
+        let operator = operators.iter().next().unwrap();
+        let expressions: Vec<String> = operator.solutions(&OperatorSet::part_2(), Evaluation::LeftToRight, true).iter().map(|ops| operator.expression(ops)).collect();
+        assert_eq!(expressions, vec![format!("{max} * 1 * 1")]);
+
+        for solver in [Solver::Forward, Solver::Reverse] {
+            let answer = calculate_value::<u64>("4: 2 2\n18446744073709551615: 9999999999 9999999999", &OperatorSet::part_2(), solver);
+            assert_eq!(answer, 4);
+        }
+    }
+
+    #[test]
+    fn test_zero_operand() {
+        let solvable = "0: 5 0\n7: 5 0 7\n0: 0 0\n50: 5 0 0\n12: 4 0 3";
+        let unsolvable = "0: 18446744073709551615 2 0\n1: 5 0";
+        for solver in [Solver::Forward, Solver::Reverse] {
+            assert!(Operators::<u64>::new(solvable).iter().all(|o| o.is_valid(&OperatorSet::part_2(), solver)), "{solver:?}");
+            assert!(Operators::<BigUint>::new(solvable).iter().all(|o| o.is_valid(&OperatorSet::part_2(), solver)), "{solver:?}");
+            assert!(!Operators::<u64>::new(unsolvable).iter().any(|o| o.is_valid(&OperatorSet::part_2(), solver)), "{solver:?}");
+        }
+        assert_eq!(calculate_value_both(solvable, &OperatorSet::part_2()), 69);
+    }
+
+    #[test]
+    fn test_big_test_values() {
+        let contents = "100000000000000000000: 10000000000 10000000000\n36893488147419103230: 18446744073709551615 18446744073709551615";
+        for solver in [Solver::Forward, Solver::Reverse] {
+            let answer = calculate_value::<BigUint>(contents, &OperatorSet::part_2(), solver);
+            assert_eq!(answer.to_string(), "136893488147419103230");
+        }
+    }
+
+    /// A test value and the numbers of one equation.
+    type Case = (u64, Vec<u64>);
+
+    fn equation<N: Number>((test_value, numbers): &Case) -> Operator<N> {
+        let number = |n: &u64| n.to_string().parse().ok().unwrap();
+        Operator { test_value: number(test_value), numbers: numbers.iter().map(number).collect() }
+    }
+
+    /// Equations where the test value is reached by some operators from `0..operators`,
+    /// just misses, or is arbitrary. Zero is picked often, as multiplying by
+    /// it cannot be undone.
+    fn cases(operators: u64) -> impl Strategy<Value = Case> {
+        prop::collection::vec(prop_oneof![1 => Just(0u64), 4 => 0..1000u64], 1..7).prop_flat_map(move |numbers| {
+            let ops = prop::collection::vec(0..operators, numbers.len() - 1);
+            let reached = (ops, -2..3i64).prop_map({
+                let numbers = numbers.clone();
+                move |(ops, offset)| (generate::evaluate(&numbers, &ops) as u64).saturating_add_signed(offset)
+            });
+            let test_value = prop_oneof![reached, 0..1_000_000u64];
+            (test_value, Just(numbers))
+        })
+    }
+
+    /// Every way of checking whether an equation can be solved with the first `operators` operators.
+    fn solver_variants(ops: OperatorSet, operators: u64) -> Variants<Case, bool> {
+        let (forward, reverse, search, big) = (ops.clone(), ops.clone(), ops.clone(), ops);
+        Variants::new()
+            .add("forward", move |case: &Case| equation::<u64>(case).is_valid(&forward, Solver::Forward))
+            .add("reverse", move |case: &Case| equation::<u64>(case).is_valid(&reverse, Solver::Reverse))
+            .add("solutions", move |case: &Case| !equation::<u64>(case).solutions(&search, Evaluation::LeftToRight, false).is_empty())
+            .add("big", move |case: &Case| equation::<BigUint>(case).is_valid(&big, Solver::Forward))
+            .add("brute force", move |(test_value, numbers): &Case| generate::solvable(*test_value, numbers, operators))
+    }
+
+    #[test]
+    fn test_solver_variants() {
+        solver_variants(OperatorSet::part_1(), 2).assert_agree(cases(2));
+        solver_variants(OperatorSet::part_2(), 3).assert_agree(cases(3));
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| match part {
+            1 => part_1::<u64>(path, Solver::Forward),
+            _ => part_2::<u64>(path, Solver::Forward),
+        }.to_string());
+    }
+}
@@ -0,0 +1,56 @@
+use num_bigint::BigUint;
+use std::{fmt, str::FromStr};
+use crate::operations::Operation;
+
+/// A number type the solvers can work with.
+///
+/// `u64` is used by default and prunes any branch that would overflow, while
+/// `BigUint` allows test values of any size.
+pub trait Number: Clone + PartialEq + fmt::Display + FromStr {
+    fn zero() -> Self;
+
+    /// Apply `op`, returning `None` when the result cannot be represented.
+    fn apply(op: &dyn Operation, lhs: &Self, rhs: &Self) -> Option<Self>;
+
+    /// Undo `op`, returning `None` when no left hand side exists.
+    fn inverse(op: &dyn Operation, result: &Self, rhs: &Self) -> Option<Self>;
+
+    /// Whether `op` gives `result` for every left hand side.
+    fn absorbs(op: &dyn Operation, result: &Self, rhs: &Self) -> bool;
+}
+
+impl Number for u64 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn apply(op: &dyn Operation, lhs: &Self, rhs: &Self) -> Option<Self> {
+        op.apply(*lhs, *rhs)
+    }
+
+    fn inverse(op: &dyn Operation, result: &Self, rhs: &Self) -> Option<Self> {
+        op.inverse(*result, *rhs)
+    }
+
+    fn absorbs(op: &dyn Operation, result: &Self, rhs: &Self) -> bool {
+        op.absorbs(*result, *rhs)
+    }
+}
+
+impl Number for BigUint {
+    fn zero() -> Self {
+        BigUint::ZERO
+    }
+
+    fn apply(op: &dyn Operation, lhs: &Self, rhs: &Self) -> Option<Self> {
+        op.apply_big(lhs, rhs)
+    }
+
+    fn inverse(op: &dyn Operation, result: &Self, rhs: &Self) -> Option<Self> {
+        op.inverse_big(result, rhs)
+    }
+
+    fn absorbs(op: &dyn Operation, result: &Self, rhs: &Self) -> bool {
+        op.absorbs_big(result, rhs)
+    }
+}
@@ -0,0 +1,311 @@
+use num_bigint::BigUint;
+use std::{fmt, str::FromStr};
+
+/// A binary operator that can be placed between two numbers of an equation.
+pub trait Operation: Sync {
+    /// Symbol used on the command line and when printing expressions.
+    fn symbol(&self) -> &'static str;
+
+    /// How tightly the operator binds when evaluating with precedence.
+    ///
+    /// Higher binds tighter, operators with equal precedence are evaluated left to right.
+    fn precedence(&self) -> u8;
+
+    /// Combine the left hand side with the next number.
+    ///
+    /// Returns `None` when the result cannot be represented, so overflowing
+    /// branches are pruned instead of wrapping.
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64>;
+
+    /// Arbitrary precision version of `apply`.
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint>;
+
+    /// Whether `inverse` is implemented for this operator.
+    fn has_inverse(&self) -> bool {
+        false
+    }
+
+    /// Find the left hand side such that `apply(lhs, rhs) == result`.
+    ///
+    /// Returns `None` when no such value exists. Operators where several left
+    /// hand sides give the same result (like `x * 0`) are treated as having
+    /// none, see `absorbs` for those.
+    fn inverse(&self, _result: u64, _rhs: u64) -> Option<u64> {
+        None
+    }
+
+    /// Arbitrary precision version of `inverse`.
+    fn inverse_big(&self, _result: &BigUint, _rhs: &BigUint) -> Option<BigUint> {
+        None
+    }
+
+    /// Whether every left hand side gives `result`, like `x * 0 == 0`.
+    fn absorbs(&self, _result: u64, _rhs: u64) -> bool {
+        false
+    }
+
+    /// Arbitrary precision version of `absorbs`.
+    fn absorbs_big(&self, _result: &BigUint, _rhs: &BigUint) -> bool {
+        false
+    }
+}
+
+pub struct Add;
+pub struct Multiply;
+pub struct Concat;
+pub struct Subtract;
+pub struct Xor;
+pub struct Power;
+
+impl Operation for Add {
+    fn symbol(&self) -> &'static str { "+" }
+
+    fn precedence(&self) -> u8 { 1 }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_add(rhs)
+    }
+
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(lhs + rhs)
+    }
+
+    fn has_inverse(&self) -> bool { true }
+
+    fn inverse(&self, result: u64, rhs: u64) -> Option<u64> {
+        result.checked_sub(rhs)
+    }
+
+    fn inverse_big(&self, result: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        (result >= rhs).then(|| result - rhs)
+    }
+}
+
+impl Operation for Multiply {
+    fn symbol(&self) -> &'static str { "*" }
+
+    fn precedence(&self) -> u8 { 2 }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_mul(rhs)
+    }
+
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(lhs * rhs)
+    }
+
+    fn has_inverse(&self) -> bool { true }
+
+    fn inverse(&self, result: u64, rhs: u64) -> Option<u64> {
+        match rhs {
+            0 => None,
+            _ if result.is_multiple_of(rhs) => Some(result / rhs),
+            _ => None,
+        }
+    }
+
+    fn inverse_big(&self, result: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        match rhs {
+            r if *r == BigUint::ZERO => None,
+            r if (result % r) == BigUint::ZERO => Some(result / r),
+            _ => None,
+        }
+    }
+
+    fn absorbs(&self, result: u64, rhs: u64) -> bool {
+        result == 0 && rhs == 0
+    }
+
+    fn absorbs_big(&self, result: &BigUint, rhs: &BigUint) -> bool {
+        *result == BigUint::ZERO && *rhs == BigUint::ZERO
+    }
+}
+
+impl Concat {
+    /// The power of ten that shifts a number left by the digits of `rhs`.
+    fn shift(rhs: u64) -> Option<u64> {
+        10u64.checked_pow(rhs.checked_ilog10().unwrap_or(0) + 1)
+    }
+
+    fn shift_big(rhs: &BigUint) -> BigUint {
+        let digits = rhs.to_str_radix(10).len();
+        BigUint::from(10u32).pow(digits as u32)
+    }
+}
+
+impl Operation for Concat {
+    fn symbol(&self) -> &'static str { "||" }
+
+    fn precedence(&self) -> u8 { 4 }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_mul(Concat::shift(rhs)?)?.checked_add(rhs)
+    }
+
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(lhs * Concat::shift_big(rhs) + rhs)
+    }
+
+    fn has_inverse(&self) -> bool { true }
+
+    fn inverse(&self, result: u64, rhs: u64) -> Option<u64> {
+        let shift = Concat::shift(rhs)?;
+        let prefix = result.checked_sub(rhs)?;
+        prefix.is_multiple_of(shift).then_some(prefix / shift)
+    }
+
+    fn inverse_big(&self, result: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        let shift = Concat::shift_big(rhs);
+        let prefix = Add.inverse_big(result, rhs)?;
+        Multiply.inverse_big(&prefix, &shift)
+    }
+}
+
+impl Operation for Subtract {
+    fn symbol(&self) -> &'static str { "-" }
+
+    fn precedence(&self) -> u8 { 1 }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_sub(rhs)
+    }
+
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Add.inverse_big(lhs, rhs)
+    }
+
+    fn has_inverse(&self) -> bool { true }
+
+    fn inverse(&self, result: u64, rhs: u64) -> Option<u64> {
+        result.checked_add(rhs)
+    }
+
+    fn inverse_big(&self, result: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(result + rhs)
+    }
+}
+
+impl Operation for Xor {
+    fn symbol(&self) -> &'static str { "^" }
+
+    fn precedence(&self) -> u8 { 0 }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        Some(lhs ^ rhs)
+    }
+
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(lhs ^ rhs)
+    }
+
+    fn has_inverse(&self) -> bool { true }
+
+    fn inverse(&self, result: u64, rhs: u64) -> Option<u64> {
+        Some(result ^ rhs)
+    }
+
+    fn inverse_big(&self, result: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(result ^ rhs)
+    }
+}
+
+impl Operation for Power {
+    fn symbol(&self) -> &'static str { "**" }
+
+    fn precedence(&self) -> u8 { 3 }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_pow(u32::try_from(rhs).ok()?)
+    }
+
+    fn apply_big(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(lhs.pow(u32::try_from(rhs).ok()?))
+    }
+}
+
+/// Precedence levels used when evaluating with precedence instead of left to right.
+#[derive(Debug, Clone, Copy)]
+pub struct Precedence {
+    /// Level of `||`, which defaults to binding tighter than every other operator.
+    pub concat: u8,
+}
+
+impl Precedence {
+    pub fn level(&self, op: &dyn Operation) -> u8 {
+        if op.symbol() == Concat.symbol() {
+            self.concat
+        }
+        else {
+            op.precedence()
+        }
+    }
+}
+
+impl Default for Precedence {
+    fn default() -> Self {
+        Precedence { concat: Concat.precedence() }
+    }
+}
+
+/// Every operator that can be selected with `--ops`.
+pub static ALL_OPERATIONS: [&dyn Operation; 6] = [&Add, &Multiply, &Concat, &Subtract, &Xor, &Power];
+
+/// An ordered set of operators the solver is allowed to use.
+#[derive(Clone)]
+pub struct OperatorSet {
+    ops: Vec<&'static dyn Operation>,
+}
+
+impl OperatorSet {
+    /// Operators for part 1: `+` and `*`.
+    pub fn part_1() -> OperatorSet {
+        OperatorSet { ops: vec![&Add, &Multiply] }
+    }
+
+    /// Operators for part 2: `+`, `*` and `||`.
+    pub fn part_2() -> OperatorSet {
+        OperatorSet { ops: vec![&Add, &Multiply, &Concat] }
+    }
+
+    /// Whether every operator in the set can be undone by the reverse solver.
+    pub fn invertible(&self) -> bool {
+        self.ops.iter().all(|op| op.has_inverse())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'static dyn Operation> + '_ {
+        self.ops.iter().copied()
+    }
+}
+
+impl FromStr for OperatorSet {
+    type Err = String;
+
+    /// Parse a comma separated list of operator symbols, e.g. `+,*,||`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ops = s.split(',').map(|symbol| {
+            let symbol = symbol.trim();
+            ALL_OPERATIONS.iter()
+                .find(|op| op.symbol() == symbol)
+                .copied()
+                .ok_or_else(|| {
+                    let known = ALL_OPERATIONS.iter().map(|op| op.symbol()).collect::<Vec<_>>().join(" ");
+                    format!("unknown operator '{symbol}' (expected one of: {known})")
+                })
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(OperatorSet { ops })
+    }
+}
+
+impl fmt::Display for OperatorSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbols: Vec<_> = self.ops.iter().map(|op| op.symbol()).collect();
+        write!(f, "{}", symbols.join(","))
+    }
+}
+
+impl fmt::Debug for OperatorSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OperatorSet({self})")
+    }
+}
@@ -0,0 +1,229 @@
+use std::{collections::{HashMap, HashSet}, str::FromStr};
+use itertools::Itertools;
+use crate::{calculate_antinodes, find_resonant, verify_location, Antenna, Location, Map};
+
+/// Antinodes produced by each frequency, keyed by the antenna id.
+pub type Antinodes<const D: usize = 2> = HashMap<char, HashSet<Location<D>>>;
+
+/// Which locations a pair of antennas with the same frequency affects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resonance {
+    /// One antinode on each side, at the distance between the antennas (part 1).
+    Pair,
+    /// Every location in line with the antennas (part 2).
+    Harmonic { exact: bool },
+}
+
+/// The bounds of the map and the antennas on it, grouped by frequency.
+///
+/// Only antennas are stored, so sparse maps with millions of empty cells stay small.
+pub struct AntennaMap<const D: usize = 2> {
+    map: Map<D>,
+    antennas: HashMap<char, Vec<Antenna<D>>>,
+    /// The frequency of the antenna at each occupied location.
+    occupied: HashMap<Location<D>, char>,
+}
+
+impl FromStr for AntennaMap {
+    type Err = String;
+
+    /// Parse a grid where `.` is empty and any other character is an antenna.
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut map = Map::new(0, 0);
+        let mut antennas = Vec::new();
+
+        for (row, line) in contents.lines().enumerate() {
+            let cols = line.chars().count();
+            if row == 0 {
+                map.size[1] = cols;
+            }
+            else if cols != map.cols() {
+                return Err(format!("Line {} has {cols} columns, expected {}.", row + 1, map.cols()));
+            }
+            map.size[0] = row + 1;
+
+            for (col, char) in line.chars().enumerate() {
+                if char != '.' {
+                    antennas.push(Antenna{id: char, location: Location::new(row as i32, col as i32)});
+                }
+            }
+        }
+
+        Ok(AntennaMap::with_antennas(map, antennas))
+    }
+}
+
+impl AntennaMap<3> {
+    /// Parse a stack of equally sized grids separated by blank lines, one per layer.
+    pub fn from_layers(contents: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut antennas = Vec::new();
+        let layers: Vec<&str> = contents.split("\n\n").filter(|l| !l.trim().is_empty()).collect();
+
+        for (layer, grid) in layers.iter().enumerate() {
+            let grid: AntennaMap = grid.parse().map_err(|e| format!("Layer {}: {e}", layer + 1))?;
+            match size {
+                None => size = Some(grid.map.size),
+                Some(s) if s != grid.map.size => {
+                    return Err(format!("Layer {} is {:?}, expected {s:?}.", layer + 1, grid.map.size));
+                }
+                Some(_) => {}
+            }
+            for antenna in grid.antennas.into_values().flatten() {
+                let [row, col] = antenna.location.coords;
+                let location = Location{coords: [layer as i32, row, col]};
+                antennas.push(Antenna{id: antenna.id, location});
+            }
+        }
+
+        let [rows, cols] = size.unwrap_or_default();
+        Ok(AntennaMap::with_antennas(Map{size: [layers.len(), rows, cols]}, antennas))
+    }
+}
+
+impl<const D: usize> AntennaMap<D> {
+    /// Build a map from antennas that are known to be on distinct locations within it.
+    fn with_antennas(map: Map<D>, antennas: Vec<Antenna<D>>) -> Self {
+        let mut antenna_map = AntennaMap{map, antennas: HashMap::new(), occupied: HashMap::new()};
+        for antenna in antennas {
+            antenna_map.occupied.insert(antenna.location.clone(), antenna.id);
+            antenna_map.antennas.entry(antenna.id).or_default().push(antenna);
+        }
+        antenna_map
+    }
+
+    /// Parse a coordinate list.
+    ///
+    /// The first line gives the size of each dimension, e.g. `12,12`, and every
+    /// following line an antenna as `frequency,coordinate,...`, e.g. `A,5,6`.
+    /// Unlike a grid the empty cells are never read, so very large sparse maps
+    /// only cost as much as their antennas.
+    pub fn from_coordinates(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines().map(str::trim).enumerate().filter(|(_, l)| !l.is_empty());
+
+        let (_, header) = lines.next().ok_or("Missing map dimensions.")?;
+        let size: Vec<usize> = header.split(',')
+            .map(|s| s.trim().parse().map_err(|_| format!("Invalid map dimension '{s}'.")))
+            .collect::<Result<_, _>>()?;
+        let size: [usize; D] = size.try_into().map_err(|s: Vec<usize>| format!("Map has {} dimensions, expected {D}.", s.len()))?;
+
+        let mut antenna_map = AntennaMap::with_antennas(Map{size}, Vec::new());
+
+        for (number, line) in lines {
+            let mut fields = line.split(',').map(str::trim);
+            let mut id = fields.next().unwrap_or_default().chars();
+            let (Some(id), None) = (id.next(), id.next()) else {
+                return Err(format!("Line {}: frequency must be a single character.", number + 1));
+            };
+            let coords: Vec<i32> = fields
+                .map(|c| c.parse().map_err(|_| format!("Line {}: invalid coordinate '{c}'.", number + 1)))
+                .collect::<Result<_, _>>()?;
+            let coords: [i32; D] = coords.try_into().map_err(|_| format!("Line {}: expected {D} coordinates.", number + 1))?;
+
+            if !antenna_map.add_antenna(Antenna{id, location: Location{coords}}) {
+                return Err(format!("Line {}: antenna is off the map or on an occupied location.", number + 1));
+            }
+        }
+
+        Ok(antenna_map)
+    }
+
+    pub fn map(&self) -> &Map<D> {
+        &self.map
+    }
+
+    /// The frequencies with at least one antenna, in sorted order.
+    pub fn frequencies(&self) -> Vec<char> {
+        self.antennas.iter()
+            .filter(|(_, antennas)| !antennas.is_empty())
+            .map(|(id, _)| *id)
+            .sorted()
+            .collect()
+    }
+
+    /// The antennas tuned to `id`.
+    pub fn antennas(&self, id: char) -> &[Antenna<D>] {
+        self.antennas.get(&id).map(|a| a.as_slice()).unwrap_or_default()
+    }
+
+    /// The antenna at `location`, if there is one.
+    pub fn antenna_at(&self, location: &Location<D>) -> Option<&Antenna<D>> {
+        let id = self.occupied.get(location)?;
+        self.antennas(*id).iter().find(|a| a.location == *location)
+    }
+
+    /// Place an antenna, returning `false` if it is off the map or the location is taken.
+    pub fn add_antenna(&mut self, antenna: Antenna<D>) -> bool {
+        if verify_location(&antenna.location, &self.map).is_none() || self.occupied.contains_key(&antenna.location) {
+            return false;
+        }
+        self.occupied.insert(antenna.location.clone(), antenna.id);
+        self.antennas.entry(antenna.id).or_default().push(antenna);
+        true
+    }
+
+    /// Remove and return the antenna at `location`.
+    pub fn remove_antenna(&mut self, location: &Location<D>) -> Option<Antenna<D>> {
+        let id = self.occupied.remove(location)?;
+        let antennas = self.antennas.get_mut(&id)?;
+        let index = antennas.iter().position(|a| a.location == *location)?;
+        Some(antennas.remove(index))
+    }
+
+    /// The antinodes of every frequency.
+    pub fn antinodes(&self, resonance: Resonance) -> Antinodes<D> {
+        self.frequencies().into_iter()
+            .map(|id| (id, self.frequency_antinodes(id, resonance)))
+            .collect()
+    }
+
+    /// The antinodes of the antennas tuned to `id`.
+    pub fn frequency_antinodes(&self, id: char, resonance: Resonance) -> HashSet<Location<D>> {
+        let mut antinodes = HashSet::new();
+
+        for comb in self.antennas(id).iter().combinations(2) {
+            let a1 = comb[0];
+            let a2 = comb[1];
+            match resonance {
+                Resonance::Pair => {
+                    let (an1, an2) = calculate_antinodes(&a1.location, &a2.location);
+                    if let Some(a) = verify_location(&an1, &self.map) {antinodes.insert(a);}
+                    if let Some(a) = verify_location(&an2, &self.map) {antinodes.insert(a);}
+                }
+                Resonance::Harmonic { exact } => {
+                    antinodes.extend(find_resonant(&a1.location, &a2.location, &self.map, exact));
+                }
+            }
+        }
+
+        antinodes
+    }
+
+    /// Find where to place one more antenna of frequency `id` to get the most antinodes overall.
+    ///
+    /// Returns the first best location in reading order and the resulting antinode count.
+    /// Every cell is tried, so this is slow on very large maps.
+    pub fn best_placement(&mut self, id: char, resonance: Resonance) -> Option<(Location<D>, usize)> {
+        let mut best: Option<(Location<D>, usize)> = None;
+
+        let map = Map{size: self.map.size};
+        for location in map.locations() {
+            if !self.add_antenna(Antenna{id, location: location.clone()}) {
+                continue;
+            }
+            let count = count_antinodes(&self.antinodes(resonance));
+            self.remove_antenna(&location);
+
+            if best.as_ref().is_none_or(|(_, c)| count > *c) {
+                best = Some((location, count));
+            }
+        }
+
+        best
+    }
+}
+
+/// Count the distinct locations that are an antinode of any frequency.
+pub fn count_antinodes<const D: usize>(antinodes: &Antinodes<D>) -> usize {
+    antinodes.values().flatten().collect::<HashSet<_>>().len()
+}
@@ -0,0 +1,86 @@
+//! Generated antenna maps with a reference solver.
+//!
+//! The size is the side of a square grid with about one antenna in every 25
+//! cells, spread over more frequencies as the grid grows.
+
+use crate::antenna_map::AntennaMap;
+use aoc::generate::{Generated, Rng};
+
+const FREQUENCIES: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+pub fn generate(size: usize, seed: u64) -> Generated {
+    let mut rng = Rng::new(seed);
+    let frequencies: Vec<char> = FREQUENCIES.chars().take(4 + size / 10).collect();
+
+    let mut grid = vec![vec!['.'; size]; size];
+    for _ in 0..(size * size / 25).max(2) {
+        grid[rng.below(size)][rng.below(size)] = *rng.choose(&frequencies);
+    }
+
+    let input: String = grid.iter().map(|row| row.iter().collect::<String>() + "\n").collect();
+    let antenna_map = crate::parse_map(&input);
+    let answers = [Harmonics::Off, Harmonics::On].map(|harmonics| reference(&antenna_map, harmonics).to_string());
+    Generated { input, answers }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Harmonics {
+    Off,
+    On,
+}
+
+/// Count the cells that are antinodes of some pair of antennas, checking every cell against every pair.
+///
+/// Without harmonics a cell is an antinode of `a` and `b` when it is as far
+/// beyond `a` as `b` is on the other side. With them, any whole number of
+/// steps from `a` to `b` counts.
+fn reference(antenna_map: &AntennaMap, harmonics: Harmonics) -> usize {
+    let antennas: Vec<(char, i64, i64)> = antenna_map.frequencies().into_iter()
+        .flat_map(|id| antenna_map.antennas(id).iter().map(move |antenna| {
+            let [row, col] = antenna.location.coords;
+            (id, row as i64, col as i64)
+        }))
+        .collect();
+
+    let is_antinode = |row: i64, col: i64| antennas.iter().any(|(id, ar, ac)| {
+        antennas.iter().any(|(other, br, bc)| {
+            if other != id || (ar, ac) == (br, bc) {
+                return false;
+            }
+            let (step_row, step_col) = (br - ar, bc - ac);
+            let (row, col) = (row - ar, col - ac);
+            match harmonics {
+                Harmonics::Off => (row, col) == (-step_row, -step_col),
+                Harmonics::On => {
+                    let k = if step_row != 0 { row / step_row } else { col / step_col };
+                    (row, col) == (k * step_row, k * step_col)
+                }
+            }
+        })
+    });
+
+    let (rows, cols) = (antenna_map.map().rows() as i64, antenna_map.map().cols() as i64);
+    (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .filter(|(row, col)| is_antinode(*row, *col))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_inputs() {
+        aoc::generate::assert_solves(0..50, |seed| 5 + seed as usize % 30, generate, |part, input| match part {
+            1 => crate::calculate_value_part_1(input),
+            _ => crate::calculate_value_part_2(input, false),
+        }.to_string());
+    }
+
+    #[test]
+    fn test_reference() {
+        let antenna_map = crate::parse_map(&crate::read_from_file(&aoc::input!("test").unwrap()));
+        assert_eq!(reference(&antenna_map, Harmonics::Off), 14);
+        assert_eq!(reference(&antenna_map, Harmonics::On), 34);
+    }
+}
@@ -1,70 +1,218 @@
-use clap::Parser;
-use std::{collections::{HashMap, HashSet}, fs};
+use clap::{Parser, ValueEnum};
+use tracing::instrument;
+use std::{collections::HashMap, fmt, fs, path::Path};
 use itertools::Itertools;
+use antenna_map::{count_antinodes, AntennaMap, Antinodes, Resonance};
+
+mod antenna_map;
+mod generate;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Filename to read the input from.
-    filename: Option<String>,
+    /// Reduce the step between two antennas by their gcd so every collinear grid point resonates.
+    #[arg(long)]
+    exact: bool,
+
+    /// Print the map with antinodes marked `#` and the antinode count of each frequency.
+    #[arg(short, long)]
+    render: bool,
+
+    /// Colour antennas and antinodes by frequency when rendering.
+    #[arg(short, long, requires = "render")]
+    color: bool,
+
+    /// Find where one more antenna of this frequency gives the most antinodes.
+    #[arg(long, value_name = "ID")]
+    best_placement: Option<char>,
+
+    /// Layout of the input file.
+    #[arg(short, long, value_enum, default_value_t = Layout::Grid)]
+    layout: Layout,
+
+    #[command(flatten)]
+    options: aoc::Options,
+
+    #[command(flatten)]
+    generate: aoc::generate::GenerateOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Layout {
+    /// A 2D grid where `.` is empty.
+    Grid,
+    /// 2D grids separated by blank lines, stacked into a 3D map.
+    Layers,
+    /// A line with the size of each dimension, then one `frequency,coordinate,...` line per antenna.
+    ///
+    /// Suited to very large sparse maps, as empty cells are never stored or scanned.
+    Coords,
 }
 
 fn main() {
 
     let cli = Args::parse();
+    aoc::init(&cli.options);
 
-    let filename = cli.filename.unwrap_or("files/test_input.txt".to_string());
+    if let Some(size) = cli.generate.size {
+        aoc::save_generated!(size, cli.generate.seed, &generate::generate(size, cli.generate.seed));
+        return;
+    }
 
-    part_1(&filename);
+    let filename = aoc::input!(cli.options.input_name()).unwrap_or_else(|e| aoc::fail(e));
+    let report = aoc::report!(&cli.options, Some(&filename));
 
-    part_2(&filename);
+    match cli.layout {
+        Layout::Grid => {
+            let mut antenna_map = parse_map(&read_from_file(&filename));
+            solve(&mut antenna_map, &cli, &report, Some(print_antinodes));
+        }
+        Layout::Layers => {
+            let mut antenna_map = AntennaMap::from_layers(&read_from_file(&filename)).unwrap_or_else(|e| panic!("Invalid map: {e}"));
+            solve(&mut antenna_map, &cli, &report, None);
+        }
+        Layout::Coords => {
+            let contents = read_from_file(&filename);
+            let header = contents.lines().find(|l| !l.trim().is_empty()).unwrap_or_default();
+            let dimensions = header.split(',').count();
+            match dimensions {
+                2 => solve(&mut parse_coordinates::<2>(&contents), &cli, &report, None),
+                3 => solve(&mut parse_coordinates::<3>(&contents), &cli, &report, None),
+                d => panic!("Maps with {d} dimensions are not supported."),
+            }
+        }
+    }
 
 }
 
-fn part_1 (filename: &str) -> i32 {
+/// How `--render` prints a map and its antinodes, for the layouts that support it.
+type Render<const D: usize> = fn(&AntennaMap<D>, Resonance, bool, &aoc::report::Report);
 
-    let contents = read_from_file(filename);
+/// Solve both parts for a map of any dimension, rendering each with `render` if asked to.
+#[instrument(skip_all)]
+fn solve<const D: usize>(antenna_map: &mut AntennaMap<D>, cli: &Args, report: &aoc::report::Report, render: Option<Render<D>>) {
+
+    if cli.render && render.is_none() {
+        eprintln!("Rendering is only supported for 2D grids.");
+    }
+    let render = render.filter(|_| cli.render);
+
+    let harmonic = Resonance::Harmonic { exact: cli.exact };
+    for (part, resonance) in [(1, Resonance::Pair), (2, harmonic)] {
+        report.part(part, "Solution", || count_antinodes(&antenna_map.antinodes(resonance)));
+        if let Some(render) = render {
+            render(antenna_map, resonance, cli.color, report);
+        }
+    }
 
-    let value = calculate_value_part_1(&contents);
+    if let Some(id) = cli.best_placement {
+        print_best_placement(antenna_map, id, cli.exact, report);
+    }
+}
 
-    println!("Part 1 Solution: {value}");
-    value
+fn print_best_placement<const D: usize>(antenna_map: &mut AntennaMap<D>, id: char, exact: bool, report: &aoc::report::Report) {
+    for (part, resonance) in [(1, Resonance::Pair), (2, Resonance::Harmonic { exact })] {
+        match antenna_map.best_placement(id, resonance) {
+            Some((location, count)) => aoc::extra!(report, "Part {part} best placement for {id}: {location} with {count} antinodes"),
+            None => aoc::extra!(report, "Part {part} best placement for {id}: no free location"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[instrument]
+fn part_1 (filename: &Path) -> i32 {
+
+    let contents = read_from_file(filename);
+
+    calculate_value_part_1(&contents)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Antenna {
+struct Antenna<const D: usize = 2> {
     id: char, 
-    location: Location,
+    location: Location<D>,
 }
 
+/// A point on a map with `D` dimensions.
+///
+/// Flat maps use `[row, col]` and layered maps `[layer, row, col]`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Location {
-    row: i32,
-    col: i32,
+struct Location<const D: usize = 2> {
+    coords: [i32; D],
 }
 
-struct Map {
-    rows: usize,
-    cols: usize,
+impl Location {
+    fn new(row: i32, col: i32) -> Location {
+        Location{coords: [row, col]}
+    }
 }
 
-fn part_2 (filename: &str) -> i32 {
+impl<const D: usize> Location<D> {
+    /// The step from `self` to `other`.
+    fn delta(&self, other: &Location<D>) -> [i32; D] {
+        std::array::from_fn(|i| other.coords[i] - self.coords[i])
+    }
 
-    let contents = read_from_file(filename);
+    /// Move by `scale` steps of `delta`.
+    fn offset(&self, delta: &[i32; D], scale: i32) -> Location<D> {
+        Location{coords: std::array::from_fn(|i| self.coords[i] + scale * delta[i])}
+    }
+}
+
+impl<const D: usize> fmt::Display for Location<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({})", self.coords.iter().join(", "))
+    }
+}
+
+/// The size of a map along each dimension.
+struct Map<const D: usize = 2> {
+    size: [usize; D],
+}
+
+impl Map {
+    fn new(rows: usize, cols: usize) -> Map {
+        Map{size: [rows, cols]}
+    }
 
-    let value = calculate_value_part_2(&contents);
+    fn rows(&self) -> usize { self.size[0] }
 
-    println!("Part 2 Solution: {value}");
-    value
+    fn cols(&self) -> usize { self.size[1] }
 }
 
-fn read_from_file(filename: &str) -> String {
+impl<const D: usize> Map<D> {
+    /// Every location on the map, with the last coordinate changing fastest.
+    fn locations(&self) -> impl Iterator<Item = Location<D>> + '_ {
+        let cells: usize = self.size.iter().product();
+        (0..cells).map(|mut index| {
+            let mut coords = [0; D];
+            for i in (0..D).rev() {
+                coords[i] = (index % self.size[i]) as i32;
+                index /= self.size[i];
+            }
+            Location{coords}
+        })
+    }
+}
+
+#[cfg(test)]
+#[instrument]
+fn part_2 (filename: &Path, exact: bool) -> i32 {
+
+    let contents = read_from_file(filename);
+
+    calculate_value_part_2(&contents, exact)
+}
+
+#[instrument]
+fn read_from_file(filename: &Path) -> String {
     fs::read_to_string(filename).expect("Unable to read file.")
 }
 
-fn verify_location(loc: &Location, map: &Map) -> Option<Location> {
+fn verify_location<const D: usize>(loc: &Location<D>, map: &Map<D>) -> Option<Location<D>> {
 
-    if loc.row >= 0 && loc.row < map.rows as i32 && loc.col >= 0 && loc.col < map.cols as i32 {
+    if loc.coords.iter().zip(map.size).all(|(c, size)| *c >= 0 && *c < size as i32) {
         Some(loc.clone())
     }
     else {
@@ -73,161 +221,371 @@ fn verify_location(loc: &Location, map: &Map) -> Option<Location> {
 
 }
 
-fn calculate_antinodes(loc1: &Location, loc2: &Location) -> (Location, Location) {
-    let delta_y = loc2.row-loc1.row;
-    let delta_x =  loc2.col-loc1.col;
+fn calculate_antinodes<const D: usize>(loc1: &Location<D>, loc2: &Location<D>) -> (Location<D>, Location<D>) {
+    let delta = loc1.delta(loc2);
 
-    let antinode1 = Location{row: loc2.row+delta_y, col: loc2.col+delta_x};
-    let antinode2 = Location{row: loc1.row-delta_y, col: loc1.col-delta_x};
+    let antinode1 = loc2.offset(&delta, 1);
+    let antinode2 = loc1.offset(&delta, -1);
 
     (antinode1, antinode2)
 }
 
-fn find_resonant(loc1: &Location, loc2: &Location, map: &Map) -> Vec<Location> {
+/// Greatest common divisor of the absolute values of `a` and `b`.
+fn gcd(a: i32, b: i32) -> i32 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Find every location in line with the two antennas.
+///
+/// The puzzle steps by the full distance between the antennas. With `exact`
+/// the step is divided by the gcd of its components, so grid points between
+/// and beyond the antennas that the puzzle step skips over are included too.
+fn find_resonant<const D: usize>(loc1: &Location<D>, loc2: &Location<D>, map: &Map<D>, exact: bool) -> Vec<Location<D>> {
     let mut rtn = Vec::new();
 
-    let delta_y = loc2.row-loc1.row;
-    let delta_x = loc2.col-loc1.col;
+    let mut delta = loc1.delta(loc2);
 
-    rtn.push(loc1.clone());
-    rtn.push(loc2.clone());
-
-    let mut next = loc2.clone();
-    loop {
-        next.row = &next.row+delta_y;
-        next.col =  &next.col+delta_x;
-        let nextv = verify_location(&next, &map);
-        match nextv {
-            Some(loc) => rtn.push(loc.clone()),
-            None => break,
-        }
+    if exact {
+        let divisor = delta.iter().fold(0, |acc, d| gcd(acc, *d));
+        delta = delta.map(|d| d / divisor);
     }
-    let mut next = loc1.clone();
-    loop {
-        next.row = &next.row-delta_y;
-        next.col =  &next.col-delta_x;
-        let nextv = verify_location(&next, &map);
-        match nextv {
-            Some(loc) => rtn.push(loc.clone()),
-            None => break,
+
+    rtn.push(loc1.clone());
+
+    for direction in [1, -1] {
+        let mut next = loc1.clone();
+        loop {
+            next = next.offset(&delta, direction);
+            let nextv = verify_location(&next, map);
+            match nextv {
+                Some(loc) => rtn.push(loc.clone()),
+                None => break,
+            }
         }
     }
 
     rtn
 }
 
-fn calculate_value_part_1(contents: &str) -> i32 {
-    let mut map = Map{rows: 0, cols: 0};
-
-    let mut antennas = HashMap::new();
+/// ANSI colour code for a frequency, so each antenna id keeps the same colour.
+fn color_code(id: char) -> u32 {
+    const CODES: [u32; 12] = [31, 32, 33, 34, 35, 36, 91, 92, 93, 94, 95, 96];
+    CODES[id as usize % CODES.len()]
+}
 
-    for (row, line) in contents.lines().enumerate() {
-        if row+1 > map.rows {
-            map.rows = row+1
-        }
-        for (col, char) in line.chars().enumerate() {
-            if col+1 > map.cols {
-                map.cols = col+1
-            }
-            match char {
-                '.' => {},
-                x => {
-                    let antenna = Antenna{id: x, location: Location{row: row as i32, col: col as i32}};
-                    let item = antennas.entry(char).or_insert(vec![]);
-                    item.push(antenna);
-                }
-            }
+/// Draw the map with `#` on every antinode, keeping the antenna glyph where they coincide.
+fn render_antinodes(antenna_map: &AntennaMap, antinodes: &Antinodes, color: bool) -> String {
+    let mut owners = HashMap::new();
+    for (id, locations) in antinodes.iter().sorted_by_key(|(id, _)| **id).rev() {
+        for location in locations {
+            owners.insert(location, *id);
         }
     }
 
-    let mut antinodes = HashSet::new();
-
-    for (_channel, antennas) in antennas.iter() {
-        for comb in antennas.iter().combinations(2) {
-            let a1 = comb[0];
-            let a2 = comb[1];
-            let (an1, an2) = calculate_antinodes(&a1.location, &a2.location);
-            let an1 = verify_location(&an1, &map);
-            let an2 = verify_location(&an2, &map);
-
-            if let Some(a) = an1 {antinodes.insert(a);}
-            if let Some(a) = an2 {antinodes.insert(a);}
+    let mut rendered = String::new();
+    for row in 0..antenna_map.map().rows() as i32 {
+        for col in 0..antenna_map.map().cols() as i32 {
+            let location = Location::new(row, col);
+            let (glyph, id) = match (antenna_map.antenna_at(&location), owners.get(&location)) {
+                (Some(antenna), _) => (antenna.id, Some(antenna.id)),
+                (None, Some(id)) => ('#', Some(*id)),
+                (None, None) => ('.', None),
+            };
+            match id {
+                Some(id) if color => rendered += &format!("\x1b[{}m{glyph}\x1b[0m", color_code(id)),
+                _ => rendered.push(glyph),
+            }
         }
+        rendered.push('\n');
     }
-
-    antinodes.len() as i32
+    rendered
 }
 
-fn calculate_value_part_2(contents: &str) -> i32 {
-    let mut map = Map{rows: 0, cols: 0};
-
-    let mut antennas = HashMap::new();
-
-    for (row, line) in contents.lines().enumerate() {
-        if row+1 > map.rows {
-            map.rows = row+1
-        }
-        for (col, char) in line.chars().enumerate() {
-            if col+1 > map.cols {
-                map.cols = col+1
-            }
-            match char {
-                '.' => {},
-                x => {
-                    let antenna = Antenna{id: x, location: Location{row: row as i32, col: col as i32}};
-                    let item = antennas.entry(char).or_insert(vec![]);
-                    item.push(antenna);
-                }
-            }
-        }
+/// Print the rendered map followed by the number of antinodes each frequency contributes.
+fn print_antinodes(antenna_map: &AntennaMap, resonance: Resonance, color: bool, report: &aoc::report::Report) {
+    let antinodes = antenna_map.antinodes(resonance);
+    aoc::extra!(report, "{}", render_antinodes(antenna_map, &antinodes, color).trim_end_matches('\n'));
+    for (id, locations) in antinodes.iter().sorted_by_key(|(id, _)| **id) {
+        aoc::extra!(report, "Frequency {id}: {} antinodes", locations.len());
     }
+}
 
-    let mut antinodes = HashSet::new();
+#[instrument(name = "parse", skip_all)]
+fn parse_map(contents: &str) -> AntennaMap {
+    contents.parse().unwrap_or_else(|e| panic!("Invalid map: {e}"))
+}
 
-    for (_channel, antennas) in antennas.iter() {
-        for comb in antennas.iter().combinations(2) {
-            let a1 = comb[0];
-            let a2 = comb[1];
-            let res = find_resonant(&a1.location, &a2.location, &map);
-            for r in res {
-                antinodes.insert(r);
-            }
-        }
-    }
+#[instrument(name = "parse", skip_all)]
+fn parse_coordinates<const D: usize>(contents: &str) -> AntennaMap<D> {
+    AntennaMap::from_coordinates(contents).unwrap_or_else(|e| panic!("Invalid map: {e}"))
+}
 
-    antinodes.len() as i32
+#[cfg(test)]
+fn calculate_value_part_1(contents: &str) -> i32 {
+    count_antinodes(&parse_map(contents).antinodes(Resonance::Pair)) as i32
+}
+
+#[cfg(test)]
+fn calculate_value_part_2(contents: &str, exact: bool) -> i32 {
+    count_antinodes(&parse_map(contents).antinodes(Resonance::Harmonic { exact })) as i32
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_args() {
+        use clap::CommandFactory;
+        Args::command().debug_assert();
+    }
+
     #[test]
     fn test_part_1_test_input() {
-        let answer = part_1("files/test_input.txt");
+        let answer = part_1(&aoc::input!("test").unwrap());
         let correct = 14;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_1_real_input() {
-        let answer = part_1("files/day_8.txt");
+        let answer = part_1(&aoc::input!("real").unwrap());
         let correct = 413;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
 
     #[test]
     fn test_part_2_test_input() {
-        let answer = part_2("files/test_input.txt");
+        let answer = part_2(&aoc::input!("test").unwrap(), false);
         let correct = 34;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
 
     #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
     fn test_part_2_real_input() {
-        let answer = part_2("files/day_8.txt");
+        let answer = part_2(&aoc::input!("real").unwrap(), false);
+        let correct = 1417;
+        assert!(answer == correct, "Answer is {answer} not {correct}.");
+    }
+
+    #[test]
+    fn test_part_2_exact() {
+        let answer = part_2(&aoc::input!("test").unwrap(), true);
+        let correct = 34;
+        assert!(answer == correct, "Answer is {answer} not {correct}.");
+    }
+
+    #[test]
+    #[cfg_attr(not(real_input), ignore = "needs files/real_input.txt or the key to decrypt it")]
+    fn test_part_2_exact_real_input() {
+        let answer = part_2(&aoc::input!("real").unwrap(), true);
         let correct = 1417;
         assert!(answer == correct, "Answer is {answer} not {correct}.");
     }
-}
 
+    #[test]
+    fn test_find_resonant_exact() {
+        let map = Map::new(5, 7);
+        let loc1 = Location::new(0, 0);
+        let loc2 = Location::new(2, 4);
+
+        let mut puzzle = find_resonant(&loc1, &loc2, &map, false);
+        puzzle.sort_by_key(|l| l.coords);
+        assert_eq!(puzzle, vec![loc1.clone(), loc2.clone()]);
+
+        let mut exact = find_resonant(&loc1, &loc2, &map, true);
+        exact.sort_by_key(|l| l.coords);
+        let correct: Vec<Location> = (0..4).map(|i| Location::new(i, 2 * i)).collect();
+        assert_eq!(exact, correct);
+    }
+
+    #[test]
+    fn test_render_antinodes() {
+        let antenna_map = parse_map(&read_from_file(&aoc::input!("test").unwrap()));
+
+        let part_1 = render_antinodes(&antenna_map, &antenna_map.antinodes(Resonance::Pair), false);
+        let correct = "\
+......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+";
+        assert_eq!(part_1, correct);
+
+        let part_2 = render_antinodes(&antenna_map, &antenna_map.antinodes(Resonance::Harmonic { exact: false }), false);
+        let correct = "\
+##....#....#
+.#.#....0...
+..#.#0....#.
+..##...0....
+....0....#..
+.#...#A....#
+...#..#.....
+#....#.#....
+..#.....A...
+....#....A..
+.#........#.
+...#......##
+";
+        assert_eq!(part_2, correct);
+    }
+
+    #[test]
+    fn test_antinodes_per_frequency() {
+        let antenna_map = parse_map(&read_from_file(&aoc::input!("test").unwrap()));
+        let antinodes = antenna_map.antinodes(Resonance::Pair);
+        assert_eq!(antinodes[&'0'].len(), 10);
+        assert_eq!(antinodes[&'A'].len(), 5);
+        assert_eq!(count_antinodes(&antinodes), 14);
+    }
+
+    #[test]
+    fn test_antenna_map() {
+        let mut antenna_map = parse_map(&read_from_file(&aoc::input!("test").unwrap()));
+        assert_eq!((antenna_map.map().rows(), antenna_map.map().cols()), (12, 12));
+        assert_eq!(antenna_map.frequencies(), vec!['0', 'A']);
+        assert_eq!(antenna_map.antennas('0').len(), 4);
+        assert!(antenna_map.antennas('b').is_empty());
+
+        let location = Location::new(0, 0);
+        assert!(antenna_map.add_antenna(Antenna{id: 'b', location: location.clone()}));
+        assert!(!antenna_map.add_antenna(Antenna{id: 'c', location: location.clone()}));
+        assert!(!antenna_map.add_antenna(Antenna{id: 'c', location: Location::new(12, 0)}));
+        assert_eq!(antenna_map.frequencies(), vec!['0', 'A', 'b']);
+
+        assert_eq!(antenna_map.remove_antenna(&location).map(|a| a.id), Some('b'));
+        assert_eq!(antenna_map.remove_antenna(&location), None);
+        assert_eq!(antenna_map.frequencies(), vec!['0', 'A']);
+    }
+
+    #[test]
+    fn test_antenna_map_unequal_lines() {
+        assert!("...\n..\n...".parse::<AntennaMap>().is_err());
+        assert!("a..\n...".parse::<AntennaMap>().is_ok());
+    }
+
+    #[test]
+    fn test_best_placement() {
+        let mut antenna_map = parse_map("a....\n.....\n.....\n.....\n.....");
+        let (location, count) = antenna_map.best_placement('a', Resonance::Harmonic { exact: false }).unwrap();
+        assert_eq!((location, count), (Location::new(0, 1), 5));
+
+        let (_, count) = antenna_map.best_placement('b', Resonance::Pair).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_coordinate_list() {
+        let antenna_map = parse_coordinates::<2>(&read_from_file(&aoc::input!("test_coords").unwrap()));
+        assert_eq!(count_antinodes(&antenna_map.antinodes(Resonance::Pair)), 14);
+        assert_eq!(count_antinodes(&antenna_map.antinodes(Resonance::Harmonic { exact: false })), 34);
+
+        assert!(AntennaMap::<2>::from_coordinates("12,12,12\nA,1,1").is_err());
+        assert!(AntennaMap::<2>::from_coordinates("12,12\nA,1").is_err());
+        assert!(AntennaMap::<2>::from_coordinates("12,12\nA,12,1").is_err());
+        assert!(AntennaMap::<2>::from_coordinates("12,12\nAB,1,1").is_err());
+    }
+
+    /// Scatter antennas over a map with a fixed LCG so the layout is repeatable.
+    fn scatter(rows: usize, cols: usize, count: usize) -> Vec<(char, usize, usize)> {
+        const IDS: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        let mut state: u64 = 8;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as usize
+        };
+        let mut taken = std::collections::HashSet::new();
+        let mut antennas = Vec::new();
+        while antennas.len() < count {
+            let (row, col) = (next() % rows, next() % cols);
+            if taken.insert((row, col)) {
+                antennas.push((IDS.chars().nth(next() % IDS.len()).unwrap(), row, col));
+            }
+        }
+        antennas
+    }
+
+    #[test]
+    fn test_sparse_coordinates_match_grid() {
+        let (rows, cols) = (2000, 2000);
+        let antennas = scatter(rows, cols, 300);
+
+        let mut coords = format!("{rows},{cols}\n");
+        let mut grid = vec![vec!['.'; cols]; rows];
+        for (id, row, col) in &antennas {
+            coords += &format!("{id},{row},{col}\n");
+            grid[*row][*col] = *id;
+        }
+        let grid: String = grid.into_iter().map(|line| line.into_iter().collect::<String>() + "\n").collect();
+
+        let sparse = parse_coordinates::<2>(&coords);
+        let dense = parse_map(&grid);
+        assert_eq!(sparse.map().size, dense.map().size);
+        for resonance in [Resonance::Pair, Resonance::Harmonic { exact: false }] {
+            assert_eq!(sparse.antinodes(resonance), dense.antinodes(resonance));
+        }
+    }
+
+    #[test]
+    fn test_layers() {
+        let single = AntennaMap::from_layers(&read_from_file(&aoc::input!("test").unwrap())).unwrap();
+        assert_eq!(single.map().size, [1, 12, 12]);
+        assert_eq!(count_antinodes(&single.antinodes(Resonance::Pair)), 14);
+        assert_eq!(count_antinodes(&single.antinodes(Resonance::Harmonic { exact: false })), 34);
+
+        let layered = AntennaMap::from_layers(&read_from_file(&aoc::input!("test_3d").unwrap())).unwrap();
+        assert_eq!(layered.map().size, [4, 4, 4]);
+        let mut antinodes: Vec<_> = layered.antinodes(Resonance::Pair).remove(&'a').unwrap().into_iter().map(|l| l.coords).collect();
+        antinodes.sort();
+        assert_eq!(antinodes, vec![[2, 2, 2]]);
+        assert_eq!(count_antinodes(&layered.antinodes(Resonance::Harmonic { exact: false })), 9);
+
+        assert!(AntennaMap::from_layers("...\n...\n\n..\n..").is_err());
+    }
+
+    #[test]
+    fn test_3d_coordinates_match_layers() {
+        let layered = AntennaMap::from_layers(&read_from_file(&aoc::input!("test_3d").unwrap())).unwrap();
+        let coords = parse_coordinates::<3>("4,4,4\na,0,0,0\na,1,1,1\nb,0,3,0\nb,1,0,1\nb,2,0,1");
+        for resonance in [Resonance::Pair, Resonance::Harmonic { exact: false }, Resonance::Harmonic { exact: true }] {
+            assert_eq!(layered.antinodes(resonance), coords.antinodes(resonance));
+        }
+    }
+
+    #[test]
+    fn test_map_locations() {
+        let map = Map{size: [2, 1, 3]};
+        let locations: Vec<_> = map.locations().map(|l| l.coords).collect();
+        assert_eq!(locations, vec![[0, 0, 0], [0, 0, 1], [0, 0, 2], [1, 0, 0], [1, 0, 1], [1, 0, 2]]);
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(2, 4), 2);
+        assert_eq!(gcd(-6, 9), 3);
+        assert_eq!(gcd(0, -5), 5);
+        assert_eq!(gcd(7, 3), 1);
+    }
+
+    #[test]
+    fn test_registered_answers() {
+        aoc::assert_registered!(|part, path| match part {
+            1 => part_1(path),
+            _ => part_2(path, false),
+        }.to_string());
+    }
+}